@@ -9,6 +9,8 @@ fn database(c: &mut Criterion) {
     let mut db = Database::new("bench.db");
     db.handle_query("new table users {id: Int, name: String};")
         .unwrap();
+    db.handle_query("new table batch {id: Int, name: String};")
+        .unwrap();
     let i_cell = Cell::new(0);
     c.bench_function("insert", |b| {
         b.iter(|| {
@@ -18,6 +20,19 @@ fn database(c: &mut Criterion) {
             i_cell.set(i + 1);
         })
     });
+    // one statement and one write for a whole batch of rows
+    let i_cell = Cell::new(0);
+    c.bench_function("insert batch", |b| {
+        b.iter(|| {
+            let i = i_cell.get();
+            let rows: Vec<String> = (i..i + 1000)
+                .map(|id| format!("{{id: {}, name: Thomas}}", id))
+                .collect();
+            db.handle_query(&format!("insert [{}] into batch;", rows.join(", ")))
+                .unwrap();
+            i_cell.set(i + 1000);
+        })
+    });
     let i_cell = Cell::new(0);
     c.bench_function("select", |b| {
         b.iter(|| {
@@ -1,6 +1,9 @@
 use colored::Colorize;
 
-use crate::database::Database;
+use crate::{
+    bql::{lexer::Lexer, parser::Parser, token::LineColumn},
+    database::Database,
+};
 
 pub mod ast;
 pub mod lexer;
@@ -9,13 +12,26 @@ pub mod token;
 
 pub fn run_file(db_file_path: &str, bql_file_path: &str) -> Result<(), String> {
     let bql_file = std::fs::read_to_string(bql_file_path).map_err(|err| err.to_string())?;
-    let mut db = Database::new(db_file_path);
 
-    for line in bql_file.lines() {
-        println!("> {}", line.dimmed());
-        match db.handle_query(line) {
+    let mut db = Database::new(db_file_path);
+    let mut parser = Parser::new(Lexer::new(&bql_file)).map_err(|err| err.to_string())?;
+    // statements run as they are parsed, so one that does not parse stops the
+    // script after those before it have run
+    loop {
+        let statement = match parser.parse_statement() {
+            Ok(Some(statement)) => statement,
+            Ok(None) => break,
+            Err(err) => {
+                let start = err.position().map_or(bql_file.len(), |p| p.start_index);
+                let start = LineColumn::from_index(&bql_file, start);
+                return Err(format!("{} {}", format!("{}", start).dimmed(), err));
+            }
+        };
+        let source = &bql_file[statement.position.start_index..=statement.position.end_index];
+        println!("> {}", source.dimmed());
+        match db.execute(statement.query) {
             Ok(out) => println!("{}", out),
-            Err(err) => eprintln!("{}", err),
+            Err(err) => eprintln!("{} {}", format!("{}", statement.start).dimmed(), err),
         }
     }
     Ok(())
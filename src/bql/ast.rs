@@ -1,12 +1,29 @@
-use crate::database::data::{Comparison, Data};
+use std::fmt;
+
+use crate::{
+    bql::token::{LineColumn, TokenPosition, TokenType},
+    database::data::{Comparison, Data, DataType},
+};
 
 #[derive(Debug)]
 pub enum Query {
-    Gimme(Gimme),
+    Gimme(Box<Gimme>),
     Tables(Tables),
     NewTable(NewTable),
     DeleteTable(DeleteTable),
+    AlterTable(AlterTable),
     Insert(Insert),
+    Update(Update),
+    Remove(Remove),
+}
+
+/// A query from a script along with where it sits in the source.
+#[derive(Debug)]
+pub struct Statement {
+    pub query: Query,
+    pub position: TokenPosition,
+    pub start: LineColumn,
+    pub end: LineColumn,
 }
 
 // TODO: remove pub from fields
@@ -16,29 +33,368 @@ pub struct Identifier {
     pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Gimme {
     pub table_identifier: Identifier,
+    pub distinct: bool,
+    pub projection: Option<Projection>,
+    pub joins: Vec<Join>,
     pub limit_statement: Option<Limit>,
+    pub offset_statement: Option<Offset>,
+    pub after_statement: Option<After>,
     pub where_statement: Option<Where>,
+    pub group_statement: Option<GroupBy>,
+    pub order_statement: Option<OrderBy>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projection {
+    pub items: Vec<ProjectionItem>,
+}
+
+/// One output column: the expression computing it and an optional name
+/// given with `as`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectionItem {
+    pub expression: Expression,
+    pub alias: Option<Identifier>,
+}
+
+impl ProjectionItem {
+    /// Name of the column the item produces in the result, the expression as
+    /// written unless it was renamed.
+    pub fn name(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.value.clone(),
+            None => self.expression.to_string(),
+        }
+    }
+}
+
+/// A value computed for each row (or group) from its fields, aggregates and
+/// literal values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Field(Identifier),
+    Literal(Data),
+    Aggregate(Aggregate),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+    /// Fields the expression reads, leaving out those inside aggregates.
+    pub fn fields(&self) -> Vec<&Identifier> {
+        match self {
+            Expression::Field(field) => vec![field],
+            Expression::Literal(_) | Expression::Aggregate(_) => Vec::new(),
+            Expression::Negate(expression) => expression.fields(),
+            Expression::Binary(left, _, right) => {
+                let mut fields = left.fields();
+                fields.extend(right.fields());
+                fields
+            }
+        }
+    }
+    pub fn aggregates(&self) -> Vec<&Aggregate> {
+        match self {
+            Expression::Aggregate(aggregate) => vec![aggregate],
+            Expression::Field(_) | Expression::Literal(_) => Vec::new(),
+            Expression::Negate(expression) => expression.aggregates(),
+            Expression::Binary(left, _, right) => {
+                let mut aggregates = left.aggregates();
+                aggregates.extend(right.aggregates());
+                aggregates
+            }
+        }
+    }
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Binary(_, operator, _) => operator.precedence(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Field(field) => write!(f, "{}", field.value),
+            Expression::Literal(data) => write!(f, "{}", data.to_literal()),
+            Expression::Aggregate(aggregate) => write!(f, "{}", aggregate),
+            Expression::Negate(expression) if expression.precedence() == u8::MAX => {
+                write!(f, "-{}", expression)
+            }
+            Expression::Negate(expression) => write!(f, "-({})", expression),
+            Expression::Binary(left, operator, right) => {
+                // parentheses only where precedence or left associativity needs them
+                match left.precedence() < operator.precedence() {
+                    true => write!(f, "({})", left)?,
+                    false => write!(f, "{}", left)?,
+                }
+                write!(f, " {} ", operator)?;
+                match right.precedence() <= operator.precedence() {
+                    true => write!(f, "({})", right),
+                    false => write!(f, "{}", right),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Operator {
+    pub fn from_token_type(token_type: &TokenType) -> Option<Self> {
+        match token_type {
+            TokenType::Plus => Some(Operator::Add),
+            TokenType::Minus => Some(Operator::Subtract),
+            TokenType::Star => Some(Operator::Multiply),
+            TokenType::Slash => Some(Operator::Divide),
+            TokenType::Percent => Some(Operator::Remainder),
+            _ => None,
+        }
+    }
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Subtract => 1,
+            Operator::Multiply | Operator::Divide | Operator::Remainder => 2,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Remainder => "%",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub field: Option<Identifier>,
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = self.field.as_ref().map_or("", |field| field.value.as_str());
+        write!(f, "{}({})", self.function, field)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "count",
+            AggregateFunction::Sum => "sum",
+            AggregateFunction::Avg => "avg",
+            AggregateFunction::Min => "min",
+            AggregateFunction::Max => "max",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Limit {
     pub number: usize,
 }
 
-#[derive(Debug, Clone)]
-pub struct Where {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offset {
+    pub number: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct After {
+    pub cursor: Identifier,
+}
+
+/// `[inner | left] join Table on a == b`, where the two fields may come in
+/// either order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table_identifier: Identifier,
+    pub on: (Identifier, Identifier),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+}
+
+/// `group by` keys, with an optional `having` filter over each group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupBy {
+    pub keys: Vec<Identifier>,
+    pub having: Option<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub keys: Vec<OrderKey>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderKey {
     pub field: Identifier,
+    pub direction: SortDirection,
+    pub nulls: NullsOrder,
+}
+
+impl fmt::Display for OrderKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        };
+        let nulls = match self.nulls {
+            NullsOrder::First => "first",
+            NullsOrder::Last => "last",
+        };
+        write!(f, "{} {} nulls {}", self.field.value, direction, nulls)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Where {
+    pub predicate: Predicate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Condition(Condition),
+    In(InCondition),
+    IsNull(NullCondition),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Fields the predicate tests, leaving out those inside aggregates.
+    pub fn fields(&self) -> Vec<&Identifier> {
+        match self {
+            Predicate::Condition(condition) => condition.expression.fields(),
+            Predicate::In(condition) => vec![&condition.field],
+            Predicate::IsNull(condition) => vec![&condition.field],
+            Predicate::And(left, right) | Predicate::Or(left, right) => {
+                let mut fields = left.fields();
+                fields.extend(right.fields());
+                fields
+            }
+            Predicate::Not(predicate) => predicate.fields(),
+        }
+    }
+    pub fn aggregates(&self) -> Vec<&Aggregate> {
+        match self {
+            Predicate::Condition(condition) => condition.expression.aggregates(),
+            Predicate::In(_) | Predicate::IsNull(_) => Vec::new(),
+            Predicate::And(left, right) | Predicate::Or(left, right) => {
+                let mut aggregates = left.aggregates();
+                aggregates.extend(right.aggregates());
+                aggregates
+            }
+            Predicate::Not(predicate) => predicate.aggregates(),
+        }
+    }
+}
+
+/// A comparison of a computed value against a literal. Conditions on
+/// aggregates are only valid in `having`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub expression: Expression,
     pub value: Data,
     pub comparison: Comparison,
 }
 
+/// `field is [not] null`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NullCondition {
+    pub field: Identifier,
+    pub negated: bool,
+}
+
+/// `field [not] in (..)`, checking the field against a list of values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InCondition {
+    pub field: Identifier,
+    pub values: InValues,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InValues {
+    List(Vec<Data>),
+    /// A single column query, replaced by the list of its values before the
+    /// outer query runs.
+    Query(Box<Gimme>),
+}
+
+/// `insert {..} into Table`, `insert [{..}, ..] into Table` or
+/// `insert from (gimme ..) into Table`, adding every row or none of them.
 #[derive(Debug, Clone)]
 pub struct Insert {
+    pub source: InsertSource,
+    pub table_identifier: Identifier,
+}
+
+#[derive(Debug, Clone)]
+pub enum InsertSource {
+    Rows(Vec<Map>),
+    /// A query whose result columns are matched to the table's by name.
+    Query(Box<Gimme>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Update {
+    pub table_identifier: Identifier,
     pub values: Map,
+    pub where_statement: Option<Where>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Remove {
     pub table_identifier: Identifier,
+    pub where_statement: Option<Where>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,7 +403,16 @@ pub struct Tables {}
 #[derive(Debug, Clone)]
 pub struct NewTable {
     pub identifier: Identifier,
-    pub fields: Map,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+/// `name: Type`, optionally followed by `null` or `not null`. Columns are
+/// nullable unless declared `not null`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: Identifier,
+    pub datatype: DataType,
+    pub nullable: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -55,6 +420,23 @@ pub struct DeleteTable {
     pub identifier: Identifier,
 }
 
+#[derive(Debug, Clone)]
+pub struct AlterTable {
+    pub identifier: Identifier,
+    pub action: AlterAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterAction {
+    /// `add column name: Type [default value]`, existing rows get the default,
+    /// `null` unless one is given.
+    AddColumn(ColumnDefinition, Data),
+    /// `drop column name`
+    DropColumn(Identifier),
+    /// `rename column name to new_name`
+    RenameColumn(Identifier, Identifier),
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct MapItem {
     pub key: Identifier,
@@ -9,6 +9,15 @@ pub enum LexerErrorReason {
     ExpectedChar((char, Option<char>)),
     InvalidCharacter(char),
     UnexpectedEOF,
+    UnterminatedString,
+    UnterminatedIdentifier,
+    UnterminatedComment,
+    EmptyIdentifier,
+    InvalidEscape(char),
+    InvalidUnicodeEscape(String),
+    MalformedNumber(String),
+    MissingDigits(String),
+    MisplacedUnderscore(String),
 }
 
 impl fmt::Display for LexerErrorReason {
@@ -23,6 +32,27 @@ impl fmt::Display for LexerErrorReason {
             }
             LexerErrorReason::InvalidCharacter(c) => write!(f, "Invalid character `{}`", c),
             LexerErrorReason::UnexpectedEOF => write!(f, "Unexpected end of input"),
+            LexerErrorReason::UnterminatedString => write!(f, "Unterminated string literal"),
+            LexerErrorReason::UnterminatedIdentifier => {
+                write!(f, "Unterminated quoted identifier")
+            }
+            LexerErrorReason::UnterminatedComment => write!(f, "Unterminated block comment"),
+            LexerErrorReason::EmptyIdentifier => write!(f, "Quoted identifier cannot be empty"),
+            LexerErrorReason::InvalidEscape(c) => write!(f, "Invalid escape sequence `\\{}`", c),
+            LexerErrorReason::InvalidUnicodeEscape(escape) => {
+                write!(f, "Invalid unicode escape `\\u{{{}}}`", escape)
+            }
+            LexerErrorReason::MalformedNumber(literal) => {
+                write!(f, "Malformed number `{}`", literal)
+            }
+            LexerErrorReason::MissingDigits(literal) => {
+                write!(f, "Number `{}` is missing digits", literal)
+            }
+            LexerErrorReason::MisplacedUnderscore(literal) => write!(
+                f,
+                "Number `{}` has an underscore that is not between two digits",
+                literal
+            ),
         }
     }
 }
@@ -42,6 +72,9 @@ impl LexerError {
             position,
         }
     }
+    pub fn position(&self) -> Option<&TokenPosition> {
+        self.position.as_ref()
+    }
 }
 
 impl fmt::Display for LexerError {
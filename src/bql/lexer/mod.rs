@@ -23,7 +23,7 @@ impl Lexer<'_> {
             current_char: None,
         };
         lexer.read_next_char();
-        return lexer;
+        lexer
     }
 
     pub fn get_input(&self) -> &str {
@@ -35,7 +35,12 @@ impl Lexer<'_> {
     }
 
     pub fn next_token(&mut self) -> Result<Token, LexerError> {
-        self.skip_whitespace();
+        loop {
+            self.skip_whitespace();
+            if !self.skip_comment()? {
+                break;
+            }
+        }
         let (current_index, current_char) = match self.current_char {
             Some(c) => c,
             None => return Err(self.build_error(LexerErrorReason::UnexpectedEOF, None)),
@@ -129,6 +134,36 @@ impl Lexer<'_> {
                     ))
                 }
             }
+            '+' => Ok(Token::new(
+                TokenType::Plus,
+                "+".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '-' => Ok(Token::new(
+                TokenType::Minus,
+                "-".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '*' => Ok(Token::new(
+                TokenType::Star,
+                "*".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '/' => Ok(Token::new(
+                TokenType::Slash,
+                "/".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '%' => Ok(Token::new(
+                TokenType::Percent,
+                "%".to_owned(),
+                start_index,
+                start_index,
+            )),
             ',' => Ok(Token::new(
                 TokenType::Comma,
                 ",".to_owned(),
@@ -147,6 +182,42 @@ impl Lexer<'_> {
                 start_index,
                 start_index,
             )),
+            '(' => Ok(Token::new(
+                TokenType::LeftParen,
+                "(".to_owned(),
+                start_index,
+                start_index,
+            )),
+            ')' => Ok(Token::new(
+                TokenType::RightParen,
+                ")".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '[' => Ok(Token::new(
+                TokenType::LeftBracket,
+                "[".to_owned(),
+                start_index,
+                start_index,
+            )),
+            ']' => Ok(Token::new(
+                TokenType::RightBracket,
+                "]".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '~' => Ok(Token::new(
+                TokenType::Tilde,
+                "~".to_owned(),
+                start_index,
+                start_index,
+            )),
+            '.' => Ok(Token::new(
+                TokenType::Dot,
+                ".".to_owned(),
+                start_index,
+                start_index,
+            )),
             ':' => Ok(Token::new(
                 TokenType::Colon,
                 ":".to_owned(),
@@ -159,32 +230,16 @@ impl Lexer<'_> {
                 start_index,
                 start_index,
             )),
+            '"' | '\'' => self.read_string(current_char, start_index),
+            '`' => self.read_quoted_identifier(start_index),
             c => {
-                if c.is_ascii_alphabetic() {
-                    let literal = self.read_while_condition(|c| c.is_ascii_alphabetic());
-                    let offset = literal.len() - 1;
+                if c.is_alphabetic() || c == '_' {
+                    let literal = self.read_while_condition(|c| c.is_alphanumeric() || c == '_');
+                    let end_index = self.current_char.map_or(start_index, |(i, _)| i);
                     let token_type = token::lookup_identifier(&literal);
-                    Ok(Token::new(
-                        token_type,
-                        literal,
-                        start_index,
-                        start_index + offset,
-                    ))
+                    Ok(Token::new(token_type, literal, start_index, end_index))
                 } else if c.is_ascii_digit() {
-                    let literal = self.read_while_condition(|c| c.is_ascii_digit() || c == '.');
-                    let offset = literal.len() - 1;
-
-                    let token_type = if literal.contains('.') {
-                        TokenType::Float
-                    } else {
-                        TokenType::Integer
-                    };
-                    Ok(Token::new(
-                        token_type,
-                        literal,
-                        start_index,
-                        start_index + offset,
-                    ))
+                    self.read_number(start_index)
                 } else {
                     Err(self.build_error(
                         LexerErrorReason::InvalidCharacter(c),
@@ -199,14 +254,172 @@ impl Lexer<'_> {
 
         self.read_next_char();
 
-        return next_token;
+        next_token
+    }
+
+    fn read_number(&mut self, start_index: usize) -> Result<Token, LexerError> {
+        let mut literal = String::new();
+        if let Some((_, c)) = self.current_char {
+            literal.push(c);
+        }
+        let is_hex = self.peek().is_some_and(|c| c == 'x' || c == 'X') && literal == "0";
+
+        // take the whole run so malformed numbers are reported as one literal
+        while let Some(next_c) = self.peek() {
+            let after_exponent = !is_hex && literal.ends_with(['e', 'E']);
+            if next_c.is_alphanumeric()
+                || next_c == '_'
+                || next_c == '.'
+                || (after_exponent && (next_c == '+' || next_c == '-'))
+            {
+                self.read_next_char();
+                literal.push(next_c);
+            } else {
+                break;
+            }
+        }
+
+        let position = TokenPosition {
+            start_index,
+            end_index: start_index + literal.len() - 1,
+        };
+        match classify_number(&literal) {
+            Ok(token_type) => Ok(Token::new(
+                token_type,
+                literal,
+                position.start_index,
+                position.end_index,
+            )),
+            Err(reason) => Err(self.build_error(reason, Some(position))),
+        }
+    }
+
+    /// Backtick-quoted identifiers are never keywords, e.g. a column named `` `limit` ``.
+    fn read_quoted_identifier(&mut self, start_index: usize) -> Result<Token, LexerError> {
+        let mut literal = String::new();
+        loop {
+            let (index, c) = match self.read_next_char() {
+                Some(c) => c,
+                None => {
+                    return Err(self.build_error(
+                        LexerErrorReason::UnterminatedIdentifier,
+                        Some(TokenPosition {
+                            start_index,
+                            end_index: self.input.len().saturating_sub(1),
+                        }),
+                    ));
+                }
+            };
+            if c != '`' {
+                literal.push(c);
+                continue;
+            }
+            if literal.is_empty() {
+                return Err(self.build_error(
+                    LexerErrorReason::EmptyIdentifier,
+                    Some(TokenPosition {
+                        start_index,
+                        end_index: index,
+                    }),
+                ));
+            }
+            return Ok(Token::new(
+                TokenType::Identifier,
+                literal,
+                start_index,
+                index,
+            ));
+        }
+    }
+
+    fn read_string(&mut self, quote: char, start_index: usize) -> Result<Token, LexerError> {
+        let mut literal = String::new();
+        loop {
+            let (index, c) = match self.read_next_char() {
+                Some(c) => c,
+                None => return Err(self.unterminated_string_error(start_index)),
+            };
+            match c {
+                '\\' => literal.push(self.read_escape(index)?),
+                c if c == quote => {
+                    return Ok(Token::new(TokenType::String, literal, start_index, index));
+                }
+                c => literal.push(c),
+            }
+        }
+    }
+
+    fn read_escape(&mut self, start_index: usize) -> Result<char, LexerError> {
+        let (index, c) = match self.read_next_char() {
+            Some(c) => c,
+            None => return Err(self.unterminated_string_error(start_index)),
+        };
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' | '"' | '\'' => Ok(c),
+            'u' => self.read_unicode_escape(start_index),
+            c => Err(self.build_error(
+                LexerErrorReason::InvalidEscape(c),
+                Some(TokenPosition {
+                    start_index,
+                    end_index: index,
+                }),
+            )),
+        }
+    }
+
+    fn read_unicode_escape(&mut self, start_index: usize) -> Result<char, LexerError> {
+        let mut hex = String::new();
+        let mut end_index = start_index + 1;
+        if let Some((index, '{')) = self.read_next_char() {
+            end_index = index;
+            while let Some((index, c)) = self.read_next_char() {
+                end_index = index;
+                if c == '}' {
+                    if let Some(c) = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .filter(|_| (1..=6).contains(&hex.len()))
+                        .and_then(char::from_u32)
+                    {
+                        return Ok(c);
+                    }
+                    break;
+                }
+                if c == '"' || c == '\'' {
+                    break;
+                }
+                hex.push(c);
+            }
+        }
+        if self.current_char.is_none() {
+            return Err(self.unterminated_string_error(start_index));
+        }
+        Err(self.build_error(
+            LexerErrorReason::InvalidUnicodeEscape(hex),
+            Some(TokenPosition {
+                start_index,
+                end_index,
+            }),
+        ))
+    }
+
+    fn unterminated_string_error(&self, start_index: usize) -> LexerError {
+        self.build_error(
+            LexerErrorReason::UnterminatedString,
+            Some(TokenPosition {
+                start_index,
+                end_index: self.input.len().saturating_sub(1),
+            }),
+        )
     }
 
     fn read_while_condition(&mut self, condition: impl Fn(char) -> bool) -> String {
         let mut out = String::new();
-        match self.current_char {
-            Some((_, c)) => out.push(c),
-            None => {}
+        if let Some((_, c)) = self.current_char {
+            out.push(c)
         };
         while let Some(next_c) = self.peek()
             && condition(next_c)
@@ -214,21 +427,49 @@ impl Lexer<'_> {
             self.read_next_char();
             out.push(next_c);
         }
-        return out;
+        out
     }
 
     fn skip_whitespace(&mut self) {
-        let whitespace_chars = vec![' ', '\n', '\t', '\r'];
-        loop {
-            let current_char = match self.current_char {
-                Some((_, c)) => c,
-                None => break,
-            };
-            if !whitespace_chars.contains(&current_char) {
-                break;
-            } else {
+        let whitespace_chars = [' ', '\n', '\t', '\r'];
+        while let Some((_, current_char)) = self.current_char
+            && whitespace_chars.contains(&current_char)
+        {
+            self.read_next_char();
+        }
+    }
+
+    /// Skips a `// line` or `/* block */` comment, returning whether one was found.
+    fn skip_comment(&mut self) -> Result<bool, LexerError> {
+        let Some((start_index, '/')) = self.current_char else {
+            return Ok(false);
+        };
+        match self.peek() {
+            Some('/') => {
+                while let Some((_, c)) = self.read_next_char()
+                    && c != '\n'
+                {}
+                Ok(true)
+            }
+            Some('*') => {
                 self.read_next_char();
+                let mut previous = None;
+                while let Some((_, c)) = self.read_next_char() {
+                    if previous == Some('*') && c == '/' {
+                        self.read_next_char();
+                        return Ok(true);
+                    }
+                    previous = Some(c);
+                }
+                Err(self.build_error(
+                    LexerErrorReason::UnterminatedComment,
+                    Some(TokenPosition {
+                        start_index,
+                        end_index: self.input.len().saturating_sub(1),
+                    }),
+                ))
             }
+            _ => Ok(false),
         }
     }
 
@@ -238,13 +479,69 @@ impl Lexer<'_> {
         } else {
             self.current_char = None;
         }
-        return self.current_char;
+        self.current_char
     }
 
     fn peek(&mut self) -> Option<char> {
-        match self.chars.peek() {
-            Some((_, peek_char)) => Some(*peek_char),
-            None => None,
+        self.chars.peek().map(|(_, peek_char)| *peek_char)
+    }
+}
+
+/// Checks a numeric literal against `digits [. digits] [e [+-] digits]` or
+/// `0x hexdigits`, where `_` may only sit between two digits.
+fn classify_number(literal: &str) -> Result<TokenType, LexerErrorReason> {
+    if let Some(digits) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        check_digits(literal, digits, |c| c.is_ascii_hexdigit())?;
+        return Ok(TokenType::Integer);
+    }
+
+    let (mantissa, exponent) = match literal.find(['e', 'E']) {
+        Some(index) => (&literal[..index], Some(&literal[index + 1..])),
+        None => (literal, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    check_digits(literal, integer, |c| c.is_ascii_digit())?;
+    if let Some(fraction) = fraction {
+        check_digits(literal, fraction, |c| c.is_ascii_digit())?;
+    }
+    if let Some(exponent) = exponent {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        check_digits(literal, exponent, |c| c.is_ascii_digit())?;
+    }
+
+    if fraction.is_some() || exponent.is_some() {
+        Ok(TokenType::Float)
+    } else {
+        Ok(TokenType::Integer)
+    }
+}
+
+fn check_digits(
+    literal: &str,
+    digits: &str,
+    is_digit: impl Fn(char) -> bool,
+) -> Result<(), LexerErrorReason> {
+    if digits.is_empty() {
+        return Err(LexerErrorReason::MissingDigits(literal.to_owned()));
+    }
+    let chars: Vec<char> = digits.chars().collect();
+    for (index, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            let previous = index.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(index + 1).copied();
+            if !previous.is_some_and(&is_digit) || !next.is_some_and(&is_digit) {
+                return Err(LexerErrorReason::MisplacedUnderscore(literal.to_owned()));
+            }
+        } else if !is_digit(*c) {
+            return Err(LexerErrorReason::MalformedNumber(literal.to_owned()));
         }
     }
+    Ok(())
 }
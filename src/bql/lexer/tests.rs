@@ -9,6 +9,48 @@ fn identifier() {
     assert_eq!(token.literal(), "foo");
 }
 
+#[test]
+fn identifier_characters() {
+    let mut lexer = Lexer::new("user_id order2024 café _private 名前");
+    let expected = vec![
+        ("user_id", 0, 6),
+        ("order2024", 8, 16),
+        ("café", 18, 21),
+        ("_private", 24, 31),
+        ("名前", 33, 36),
+    ];
+    for (literal, start_index, end_index) in expected {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), TokenType::Identifier);
+        assert_eq!(token.literal(), literal);
+        assert_eq!(token.position().start_index, start_index);
+        assert_eq!(token.position().end_index, end_index);
+    }
+}
+
+#[test]
+fn quoted_identifier() {
+    let mut lexer = Lexer::new("`gimme` `weird name` gimme");
+    let expected = vec![
+        (TokenType::Identifier, "gimme"),
+        (TokenType::Identifier, "weird name"),
+        (TokenType::Gimme, "gimme"),
+    ];
+    for (token_type, literal) in expected {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), token_type);
+        assert_eq!(token.literal(), literal);
+    }
+
+    let err = Lexer::new("`open").next_token().unwrap_err();
+    assert!(matches!(
+        err.reason,
+        LexerErrorReason::UnterminatedIdentifier
+    ));
+    let err = Lexer::new("``").next_token().unwrap_err();
+    assert!(matches!(err.reason, LexerErrorReason::EmptyIdentifier));
+}
+
 #[test]
 fn keyword() {
     let mut lexer = Lexer::new("gimme");
@@ -17,6 +59,17 @@ fn keyword() {
     assert_eq!(token.literal(), "gimme");
 }
 
+#[test]
+fn contextual_keyword() {
+    // only reserved words get their own token, the rest are matched by the parser
+    let mut lexer = Lexer::new("order by first");
+    for literal in ["order", "by", "first"] {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), TokenType::Identifier);
+        assert_eq!(token.literal(), literal);
+    }
+}
+
 #[test]
 fn integer() {
     let mut lexer = Lexer::new("123");
@@ -33,6 +86,109 @@ fn float() {
     assert_eq!(token.literal(), "3.14");
 }
 
+#[test]
+fn number_syntax() {
+    let mut lexer = Lexer::new("1_000_000 0xFF 0xf_f 1e9 2.5E-3 1_0.0_1 -5");
+    let tokens = vec![
+        (TokenType::Integer, "1_000_000"),
+        (TokenType::Integer, "0xFF"),
+        (TokenType::Integer, "0xf_f"),
+        (TokenType::Float, "1e9"),
+        (TokenType::Float, "2.5E-3"),
+        (TokenType::Float, "1_0.0_1"),
+        (TokenType::Minus, "-"),
+        (TokenType::Integer, "5"),
+    ];
+    for (token_type, literal) in tokens {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), token_type);
+        assert_eq!(token.literal(), literal);
+    }
+}
+
+#[test]
+fn malformed_numbers() {
+    let inputs = vec![
+        ("1.2.3", "malformed"),
+        ("12abc", "malformed"),
+        ("0xFG", "malformed"),
+        ("0x", "missing"),
+        ("1.", "missing"),
+        ("1e", "missing"),
+        ("1e+", "missing"),
+        ("1__0", "underscore"),
+        ("1_", "underscore"),
+        ("1_.5", "underscore"),
+        ("0x_ff", "underscore"),
+    ];
+    for (input, kind) in inputs {
+        let mut lexer = Lexer::new(input);
+        let err = lexer.next_token().unwrap_err();
+        match kind {
+            "malformed" => assert!(matches!(err.reason, LexerErrorReason::MalformedNumber(_))),
+            "missing" => assert!(matches!(err.reason, LexerErrorReason::MissingDigits(_))),
+            _ => assert!(matches!(
+                err.reason,
+                LexerErrorReason::MisplacedUnderscore(_)
+            )),
+        }
+        let position = err.position().unwrap();
+        assert_eq!(position.start_index, 0);
+        assert_eq!(position.end_index, input.len() - 1);
+    }
+}
+
+#[test]
+fn string() {
+    let mut lexer = Lexer::new(r#""Thomas Forbes" 'o\'brien' "a@b.com, 42!""#);
+    let expected = vec![
+        ("Thomas Forbes", 0, 14),
+        ("o'brien", 16, 25),
+        ("a@b.com, 42!", 27, 40),
+    ];
+    for (literal, start_index, end_index) in expected {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), TokenType::String);
+        assert_eq!(token.literal(), literal);
+        assert_eq!(token.position().start_index, start_index);
+        assert_eq!(token.position().end_index, end_index);
+    }
+}
+
+#[test]
+fn string_escapes() {
+    let mut lexer = Lexer::new(r#""a\nb\tc\\d\"e\u{e9}\u{1F34C}""#);
+    let token = lexer.next_token().unwrap();
+    assert_eq!(token.literal(), "a\nb\tc\\d\"e\u{e9}\u{1F34C}");
+}
+
+#[test]
+fn string_errors() {
+    let inputs = vec![
+        (r#""never closed"#, 0),
+        (r#"'bad \q escape'"#, 5),
+        (r#""\u{110000}""#, 1),
+        (r#""\u{zz}""#, 1),
+        (r#""\u{}""#, 1),
+        (r#""dangling \"#, 10),
+    ];
+    for (input, start_index) in inputs {
+        let mut lexer = Lexer::new(input);
+        let err = lexer.next_token().unwrap_err();
+        assert!(matches!(
+            err.reason,
+            LexerErrorReason::UnterminatedString
+                | LexerErrorReason::InvalidEscape(_)
+                | LexerErrorReason::InvalidUnicodeEscape(_)
+        ));
+        assert_eq!(err.position().unwrap().start_index, start_index);
+    }
+    let err = Lexer::new(r#""open"#).next_token().unwrap_err();
+    assert!(matches!(err.reason, LexerErrorReason::UnterminatedString));
+    let err = Lexer::new(r#""\q""#).next_token().unwrap_err();
+    assert!(matches!(err.reason, LexerErrorReason::InvalidEscape('q')));
+}
+
 #[test]
 fn comparison() {
     let mut lexer = Lexer::new("== != <= < > >=");
@@ -50,6 +206,71 @@ fn comparison() {
     }
 }
 
+#[test]
+fn logical() {
+    let mut lexer = Lexer::new("and or not ( )");
+    let tokens = vec![
+        TokenType::And,
+        TokenType::Or,
+        TokenType::Not,
+        TokenType::LeftParen,
+        TokenType::RightParen,
+    ];
+    for expected_token in tokens {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), expected_token);
+    }
+}
+
+#[test]
+fn arithmetic() {
+    let mut lexer = Lexer::new("a+b - c*d/e % 2");
+    let tokens = vec![
+        TokenType::Identifier,
+        TokenType::Plus,
+        TokenType::Identifier,
+        TokenType::Minus,
+        TokenType::Identifier,
+        TokenType::Star,
+        TokenType::Identifier,
+        TokenType::Slash,
+        TokenType::Identifier,
+        TokenType::Percent,
+        TokenType::Integer,
+    ];
+    for expected_token in tokens {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), expected_token);
+    }
+}
+
+#[test]
+fn comments() {
+    let mut lexer = Lexer::new(
+        "// leading comment\ngimme /* inline */ Users; // default limit of 1\n/* multi\n * line */",
+    );
+    let tokens = vec![
+        TokenType::Gimme,
+        TokenType::Identifier,
+        TokenType::Semicolon,
+    ];
+    for expected_token in tokens {
+        let token = lexer.next_token().unwrap();
+        assert_eq!(*token.token_type(), expected_token);
+    }
+    let err = lexer.next_token().unwrap_err();
+    assert!(matches!(err.reason, LexerErrorReason::UnexpectedEOF));
+}
+
+#[test]
+fn unterminated_comment() {
+    let mut lexer = Lexer::new("gimme /* never closed");
+    lexer.next_token().unwrap();
+    let err = lexer.next_token().unwrap_err();
+    assert!(matches!(err.reason, LexerErrorReason::UnterminatedComment));
+    assert_eq!(err.position().unwrap().start_index, 6);
+}
+
 #[test]
 fn invalid_character() {
     let mut lexer = Lexer::new("$");
@@ -67,11 +288,11 @@ fn gimme_statement() {
     let tokens = vec![
         Token::new(TokenType::Gimme, "gimme".to_string(), 0, 0),
         Token::new(TokenType::Identifier, "users".to_string(), 0, 0),
-        Token::new(TokenType::Where, "where".to_string(), 0, 0),
+        Token::new(TokenType::Identifier, "where".to_string(), 0, 0),
         Token::new(TokenType::Identifier, "age".to_string(), 0, 0),
         Token::new(TokenType::GreaterEquals, ">=".to_string(), 0, 0),
         Token::new(TokenType::Integer, "18".to_string(), 0, 0),
-        Token::new(TokenType::Limit, "limit".to_string(), 0, 0),
+        Token::new(TokenType::Identifier, "limit".to_string(), 0, 0),
         Token::new(TokenType::Integer, "10".to_string(), 0, 0),
         Token::new(TokenType::Semicolon, ";".to_string(), 0, 0),
     ];
@@ -104,7 +325,7 @@ fn insert_statement() {
         Token::new(TokenType::Colon, ":".to_string(), 0, 0),
         Token::new(TokenType::False, "false".to_string(), 0, 0),
         Token::new(TokenType::RightBrace, "}".to_string(), 0, 0),
-        Token::new(TokenType::Into, "into".to_string(), 0, 0),
+        Token::new(TokenType::Identifier, "into".to_string(), 0, 0),
         Token::new(TokenType::Identifier, "users".to_string(), 0, 0),
         Token::new(TokenType::Semicolon, ";".to_string(), 0, 0),
     ];
@@ -24,6 +24,12 @@ impl ParseError {
             position,
         }
     }
+    pub fn position(&self) -> Option<&TokenPosition> {
+        match &self.reason {
+            ParseErrorReason::LexerError(e) => e.position(),
+            _ => self.position.as_ref(),
+        }
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -59,10 +65,17 @@ impl std::error::Error for ParseError {}
 
 #[derive(Debug, Clone)]
 pub enum ParseErrorReason {
-    LexerError(LexerError),
+    LexerError(Box<LexerError>),
     InvalidStartOfStatement(String),
     ExpectedToken((TokenType, Option<TokenType>)),
+    ExpectedKeyword((Option<String>, &'static str)),
     UnexpectedEOF(Option<TokenType>),
+    UnfilteredRemove,
+    IntegerOutOfRange(String),
+    UnknownFunction(String),
+    InvalidRegex(String),
+    ExpectedField(String),
+    ExpectedAlterAction(String),
 }
 
 impl fmt::Display for ParseErrorReason {
@@ -79,6 +92,10 @@ impl fmt::Display for ParseErrorReason {
                 }
                 Ok(())
             }
+            ParseErrorReason::ExpectedKeyword((received, expected)) => match received {
+                Some(received) => write!(f, "Received `{}` but expected `{}`", received, expected),
+                None => write!(f, "Expected `{}` but got EOF", expected),
+            },
             ParseErrorReason::UnexpectedEOF(expected) => {
                 if let Some(expected) = expected {
                     write!(f, "Expected `{:?}` but got EOF", expected)?;
@@ -87,6 +104,27 @@ impl fmt::Display for ParseErrorReason {
                 }
                 Ok(())
             }
+            ParseErrorReason::IntegerOutOfRange(literal) => {
+                write!(f, "Integer `{}` does not fit in 64 bits", literal)
+            }
+            ParseErrorReason::UnknownFunction(name) => {
+                write!(f, "`{}` is not a known function", name)
+            }
+            ParseErrorReason::InvalidRegex(error) => {
+                write!(f, "Invalid regular expression: {}", error)
+            }
+            ParseErrorReason::ExpectedField(keyword) => {
+                write!(f, "`{}` can only follow a column name", keyword)
+            }
+            ParseErrorReason::ExpectedAlterAction(literal) => write!(
+                f,
+                "Expected `add column`, `drop column` or `rename column` but got `{}`",
+                literal
+            ),
+            ParseErrorReason::UnfilteredRemove => write!(
+                f,
+                "`remove` without `where` would delete every row, use `remove all from` instead"
+            ),
         }
     }
 }
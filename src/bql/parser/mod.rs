@@ -5,17 +5,30 @@ mod tests;
 use crate::{
     bql::{
         ast::*,
-        lexer::{Lexer, error::LexerErrorReason},
+        lexer::{
+            Lexer,
+            error::{LexerError, LexerErrorReason},
+        },
         parser::error::{ParseError, ParseErrorReason},
-        token::{Token, TokenType},
+        token::{LineColumn, Token, TokenPosition, TokenType},
     },
-    database::data::{Comparison, Data},
+    database::data::{Comparison, Data, DataType, Pattern},
 };
 
+/// What a parenthesized group at the start of a condition turned out to hold.
+enum Parenthesized {
+    Predicate(Predicate),
+    Expression(Expression),
+}
+
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     peek_token: Option<Token>,
     current_token: Option<Token>,
+    /// Where the lexer failed, in place of the tokens after the last good one.
+    /// It is only raised when the parser goes looking for one of those tokens,
+    /// so the statements before it still parse.
+    lexer_error: Option<LexerError>,
 }
 
 impl Parser<'_> {
@@ -24,26 +37,37 @@ impl Parser<'_> {
             lexer,
             peek_token: None,
             current_token: None,
+            lexer_error: None,
         };
         parser.next_token()?;
         parser.next_token()?;
-        return Ok(parser);
+        Ok(parser)
     }
 
     // HELPERS
     fn next_token(&mut self) -> Result<(), ParseError> {
-        self.current_token = self.peek_token.clone();
-        self.peek_token = match self.lexer.next_token() {
-            Ok(token) => Some(token),
-            Err(e) => match e.reason {
-                LexerErrorReason::UnexpectedEOF => None,
-                _ => {
-                    return Err(self.build_error(ParseErrorReason::LexerError(e), &self.peek_token));
-                }
-            },
-        };
+        self.current_token = self.peek_token.take();
+        if self.lexer_error.is_some() {
+            return Ok(());
+        }
+        match self.lexer.next_token() {
+            Ok(token) => self.peek_token = Some(token),
+            Err(e) if matches!(e.reason, LexerErrorReason::UnexpectedEOF) => {}
+            Err(e) => self.lexer_error = Some(e),
+        }
         Ok(())
     }
+    /// Error for a token that isn't there, the lexer's if it stopped early.
+    fn missing_token(&self, reason: ParseErrorReason, token: &Option<Token>) -> ParseError {
+        match &self.lexer_error {
+            Some(e) => ParseError::new(
+                self.lexer.get_input().to_owned(),
+                ParseErrorReason::LexerError(Box::new(e.clone())),
+                e.position().cloned(),
+            ),
+            None => self.build_error(reason, token),
+        }
+    }
     fn build_error(&self, reason: ParseErrorReason, token: &Option<Token>) -> ParseError {
         ParseError::new(
             self.lexer.get_input().to_owned(),
@@ -52,50 +76,83 @@ impl Parser<'_> {
         )
     }
     fn get_current_token(&self) -> Result<&Token, ParseError> {
-        self.current_token
-            .as_ref()
-            .ok_or(self.build_error(ParseErrorReason::UnexpectedEOF(None), &self.current_token))
+        self.current_token.as_ref().ok_or_else(|| {
+            self.missing_token(ParseErrorReason::UnexpectedEOF(None), &self.current_token)
+        })
     }
     fn current_token_is(&self, token_type: TokenType) -> Result<&Token, ParseError> {
         if let Some(current_token) = &self.current_token {
             if current_token.token_type() == &token_type {
-                return Ok(current_token);
+                Ok(current_token)
             } else {
-                return Err(self.build_error(
+                Err(self.build_error(
                     ParseErrorReason::ExpectedToken((
                         current_token.token_type().clone(),
                         Some(token_type),
                     )),
                     &self.current_token,
-                ));
+                ))
             }
         } else {
-            return Err(self.build_error(
+            Err(self.missing_token(
                 ParseErrorReason::UnexpectedEOF(Some(token_type)),
                 &self.current_token,
-            ));
+            ))
         }
     }
     fn peek_token_is(&self, token_type: TokenType) -> Result<&Token, ParseError> {
         if let Some(peek_token) = &self.peek_token {
             if peek_token.token_type() == &token_type {
-                return Ok(peek_token);
+                Ok(peek_token)
             } else {
-                return Err(self.build_error(
+                Err(self.build_error(
                     ParseErrorReason::ExpectedToken((
                         peek_token.token_type().clone(),
                         Some(token_type),
                     )),
                     &self.peek_token,
-                ));
+                ))
             }
         } else {
-            return Err(self.build_error(
+            Err(self.missing_token(
                 ParseErrorReason::UnexpectedEOF(Some(token_type)),
                 &self.peek_token,
-            ));
+            ))
+        }
+    }
+    /// Contextual keywords like `order` or `first` lex as identifiers so they
+    /// stay usable as names, and are only keywords where the grammar expects them.
+    fn current_keyword_is(&self, keyword: &'static str) -> Result<&Token, ParseError> {
+        self.keyword_is(&self.current_token, keyword)
+    }
+    fn peek_keyword_is(&self, keyword: &'static str) -> Result<&Token, ParseError> {
+        self.keyword_is(&self.peek_token, keyword)
+    }
+    fn keyword_is<'t>(
+        &self,
+        token: &'t Option<Token>,
+        keyword: &'static str,
+    ) -> Result<&'t Token, ParseError> {
+        match token {
+            Some(found)
+                if found.token_type() == &TokenType::Identifier && found.literal() == keyword =>
+            {
+                Ok(found)
+            }
+            Some(found) => Err(self.build_error(
+                ParseErrorReason::ExpectedKeyword((Some(found.literal().clone()), keyword)),
+                token,
+            )),
+            None => {
+                Err(self.missing_token(ParseErrorReason::ExpectedKeyword((None, keyword)), token))
+            }
         }
     }
+    fn expect_peek_keyword(&mut self, keyword: &'static str) -> Result<Token, ParseError> {
+        let peek_token = self.peek_keyword_is(keyword)?.clone();
+        self.next_token()?;
+        Ok(peek_token)
+    }
     fn expect_current(&mut self, token_type: TokenType) -> Result<Token, ParseError> {
         let current_token = self.current_token_is(token_type)?.clone();
         self.next_token()?;
@@ -111,18 +168,55 @@ impl Parser<'_> {
     pub fn parse_query(&mut self) -> Result<Query, ParseError> {
         let current_token = self.get_current_token()?;
         let query = match current_token.token_type() {
-            TokenType::Gimme => self.parse_gimme().map(|g| Query::Gimme(g)),
-            TokenType::Tables => self.parse_tables().map(|t| Query::Tables(t)),
-            TokenType::New => self.parse_new_table().map(|t| Query::NewTable(t)),
-            TokenType::Delete => self.parse_delete_table().map(|t| Query::DeleteTable(t)),
-            TokenType::Insert => self.parse_insert().map(|i| Query::Insert(i)),
+            TokenType::Gimme => self
+                .parse_gimme()
+                .map(|gimme| Query::Gimme(Box::new(gimme))),
+            TokenType::Tables => self.parse_tables().map(Query::Tables),
+            TokenType::New => self.parse_new_table().map(Query::NewTable),
+            TokenType::Delete => self.parse_delete_table().map(Query::DeleteTable),
+            TokenType::Alter => self.parse_alter_table().map(Query::AlterTable),
+            TokenType::Insert => self.parse_insert().map(Query::Insert),
+            TokenType::Update => self.parse_update().map(Query::Update),
+            TokenType::Remove => self.parse_remove().map(Query::Remove),
             _ => Err(self.build_error(
                 ParseErrorReason::InvalidStartOfStatement(current_token.literal().clone()),
                 &self.current_token,
             )),
-        };
+        }?;
         self.expect_peek(TokenType::Semicolon)?;
-        return query;
+        Ok(query)
+    }
+    pub fn parse_script(&mut self) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = Vec::new();
+        while let Some(statement) = self.parse_statement()? {
+            statements.push(statement);
+        }
+        Ok(statements)
+    }
+    /// Parses the next statement of a script, `None` once the input is used up.
+    pub fn parse_statement(&mut self) -> Result<Option<Statement>, ParseError> {
+        let Some(first_token) = &self.current_token else {
+            return match self.lexer_error {
+                Some(_) => Err(self.missing_token(ParseErrorReason::UnexpectedEOF(None), &None)),
+                None => Ok(None),
+            };
+        };
+        let start_index = first_token.position().start_index;
+        let query = self.parse_query()?;
+        let end_index = self.get_current_token()?.position().end_index;
+
+        let input = self.lexer.get_input();
+        let statement = Statement {
+            query,
+            position: TokenPosition {
+                start_index,
+                end_index,
+            },
+            start: LineColumn::from_index(input, start_index),
+            end: LineColumn::from_index(input, end_index),
+        };
+        self.next_token()?; // moves past ;
+        Ok(Some(statement))
     }
 
     fn parse_identifier(&self) -> Result<Identifier, ParseError> {
@@ -132,23 +226,52 @@ impl Parser<'_> {
             value: token.literal().clone(),
         })
     }
+    /// Parses a column name, optionally qualified by its table as in `Users.id`.
+    fn parse_field(&mut self) -> Result<Identifier, ParseError> {
+        let mut identifier = self.parse_identifier()?;
+        if self.peek_token_is(TokenType::Dot).is_ok() {
+            self.next_token()?;
+            self.expect_peek(TokenType::Identifier)?;
+            identifier.value = format!("{}.{}", identifier.value, self.parse_identifier()?.value);
+        }
+        Ok(identifier)
+    }
     fn parse_integer(&self) -> Result<i64, ParseError> {
+        self.parse_signed_integer(false)
+    }
+    fn parse_signed_integer(&self, negative: bool) -> Result<i64, ParseError> {
         let token = self.get_current_token()?;
-
-        token.literal().parse().map_err(|_| {
-            self.build_error(
+        if token.token_type() != &TokenType::Integer {
+            return Err(self.build_error(
                 ParseErrorReason::ExpectedToken((
                     token.token_type().clone(),
                     Some(TokenType::Integer),
                 )),
                 &self.current_token,
+            ));
+        }
+
+        let literal = token.literal().replace('_', "");
+        let (digits, radix) = match literal
+            .strip_prefix("0x")
+            .or_else(|| literal.strip_prefix("0X"))
+        {
+            Some(digits) => (digits, 16),
+            None => (literal.as_str(), 10),
+        };
+        let sign = if negative { "-" } else { "" };
+
+        i64::from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|_| {
+            self.build_error(
+                ParseErrorReason::IntegerOutOfRange(format!("{}{}", sign, token.literal())),
+                &self.current_token,
             )
         })
     }
     fn parse_float(&self) -> Result<f64, ParseError> {
         let token = self.get_current_token()?;
 
-        token.literal().parse().map_err(|_| {
+        token.literal().replace('_', "").parse().map_err(|_| {
             self.build_error(
                 ParseErrorReason::ExpectedToken((
                     token.token_type().clone(),
@@ -158,26 +281,86 @@ impl Parser<'_> {
             )
         })
     }
-    fn parse_data(&self) -> Result<Data, ParseError> {
+    fn parse_data(&mut self) -> Result<Data, ParseError> {
         let current_token = self.get_current_token()?;
         match current_token.token_type() {
+            TokenType::Minus => {
+                self.next_token()?;
+                let current_token = self.get_current_token()?;
+                match current_token.token_type() {
+                    TokenType::Integer => Ok(Data::Int(Some(self.parse_signed_integer(true)?))),
+                    TokenType::Float => Ok(Data::Float(Some(-self.parse_float()?))),
+                    _ => Err(self.build_error(
+                        ParseErrorReason::ExpectedToken((
+                            current_token.token_type().clone(),
+                            Some(TokenType::Integer),
+                        )),
+                        &self.current_token,
+                    )),
+                }
+            }
             // data values
-            TokenType::Identifier => Ok(Data::String(Some(current_token.literal().clone()))),
+            TokenType::Identifier | TokenType::String => {
+                Ok(Data::String(Some(current_token.literal().clone())))
+            }
             TokenType::Integer => Ok(Data::Int(Some(self.parse_integer()?))),
             TokenType::Float => Ok(Data::Float(Some(self.parse_float()?))),
             TokenType::True => Ok(Data::Boolean(Some(true))),
             TokenType::False => Ok(Data::Boolean(Some(false))),
-            // data types
-            TokenType::IntWord => Ok(Data::Int(None)),
-            TokenType::StringWord => Ok(Data::String(None)),
-            TokenType::FloatWord => Ok(Data::Float(None)),
-            TokenType::BooleanWord => Ok(Data::Boolean(None)),
+            TokenType::Null => Ok(Data::Null),
             _ => Err(self.build_error(
                 ParseErrorReason::ExpectedToken((current_token.token_type().clone(), None)),
                 &self.current_token,
             )),
         }
     }
+    fn parse_datatype(&self) -> Result<DataType, ParseError> {
+        let current_token = self.get_current_token()?;
+        match current_token.token_type() {
+            TokenType::IntWord => Ok(DataType::Int),
+            TokenType::StringWord => Ok(DataType::String),
+            TokenType::FloatWord => Ok(DataType::Float),
+            TokenType::BooleanWord => Ok(DataType::Boolean),
+            _ => Err(self.build_error(
+                ParseErrorReason::ExpectedToken((current_token.token_type().clone(), None)),
+                &self.current_token,
+            )),
+        }
+    }
+    fn parse_column_definition(&mut self) -> Result<ColumnDefinition, ParseError> {
+        self.current_token_is(TokenType::Identifier)?;
+        let name = self.parse_identifier()?;
+        self.expect_peek(TokenType::Colon)?;
+        self.next_token()?;
+        let datatype = self.parse_datatype()?;
+
+        let mut nullable = true;
+        if self.peek_token_is(TokenType::Not).is_ok() {
+            self.next_token()?;
+            self.expect_peek(TokenType::Null)?;
+            nullable = false;
+        } else if self.peek_token_is(TokenType::Null).is_ok() {
+            self.next_token()?;
+        }
+        Ok(ColumnDefinition {
+            name,
+            datatype,
+            nullable,
+        })
+    }
+    fn parse_column_definitions(&mut self) -> Result<Vec<ColumnDefinition>, ParseError> {
+        let mut columns = Vec::new();
+        self.expect_current(TokenType::LeftBrace)?;
+
+        while self.current_token_is(TokenType::RightBrace).is_err() {
+            if self.current_token_is(TokenType::Comma).is_ok() {
+                self.next_token()?;
+            }
+            columns.push(self.parse_column_definition()?);
+            self.next_token()?; // moves to , or }
+        }
+        Ok(columns)
+    }
     fn parse_map(&mut self) -> Result<Map, ParseError> {
         let mut map = Vec::new();
         self.expect_current(TokenType::LeftBrace)?;
@@ -201,25 +384,240 @@ impl Parser<'_> {
 
     // GIMME
     fn parse_gimme(&mut self) -> Result<Gimme, ParseError> {
-        self.expect_peek(TokenType::Identifier)?;
+        self.next_token()?;
+        let distinct = self.current_keyword_is("distinct").is_ok();
+        if distinct {
+            self.next_token()?;
+        }
+
+        // `gimme {..} from Table` and `gimme count() from Table` name the columns first
+        let mut projection = None;
+        if self.current_token_is(TokenType::LeftBrace).is_ok() {
+            projection = Some(self.parse_projection()?);
+        } else if self.current_token_is(TokenType::Identifier).is_ok()
+            && self.peek_token_is(TokenType::LeftParen).is_ok()
+        {
+            projection = Some(Projection {
+                items: vec![self.parse_projection_item()?],
+            });
+        }
+        if projection.is_some() {
+            self.expect_peek_keyword("from")?;
+            self.next_token()?;
+        }
+
+        self.current_token_is(TokenType::Identifier)?;
         let identifier = self.parse_identifier()?;
 
+        let mut joins = Vec::new();
         let mut where_statement = None;
+        let mut group_statement = None;
+        let mut order_statement = None;
         let mut limit_statement = None;
-        if self.peek_token_is(TokenType::Where).is_ok() {
+        let mut offset_statement = None;
+        let mut after_statement = None;
+        if projection.is_none() && self.peek_token_is(TokenType::LeftBrace).is_ok() {
+            self.next_token()?;
+            projection = Some(self.parse_projection()?);
+        }
+        while self.peek_keyword_is("join").is_ok()
+            || self.peek_keyword_is("inner").is_ok()
+            || self.peek_keyword_is("left").is_ok()
+        {
+            self.next_token()?;
+            joins.push(self.parse_join()?);
+        }
+        if self.peek_keyword_is("where").is_ok() {
             self.next_token()?;
             where_statement = Some(self.parse_where()?);
         }
-        if self.peek_token_is(TokenType::Limit).is_ok() {
+        if self.peek_keyword_is("group").is_ok() {
+            self.next_token()?;
+            group_statement = Some(self.parse_group_by()?);
+        }
+        if self.peek_keyword_is("order").is_ok() {
+            self.next_token()?;
+            order_statement = Some(self.parse_order_by()?);
+        }
+        if self.peek_keyword_is("limit").is_ok() {
             self.next_token()?;
             limit_statement = Some(self.parse_limit()?);
         }
+        if self.peek_keyword_is("offset").is_ok() {
+            self.next_token()?;
+            offset_statement = Some(self.parse_offset()?);
+        }
+        if self.peek_keyword_is("after").is_ok() {
+            self.next_token()?;
+            after_statement = Some(self.parse_after()?);
+        }
         Ok(Gimme {
             table_identifier: identifier,
+            distinct,
+            projection,
+            joins,
             limit_statement,
+            offset_statement,
+            after_statement,
             where_statement,
+            group_statement,
+            order_statement,
         })
     }
+    fn parse_projection(&mut self) -> Result<Projection, ParseError> {
+        let mut items = Vec::new();
+        self.expect_current(TokenType::LeftBrace)?;
+
+        while self.current_token_is(TokenType::RightBrace).is_err() {
+            if self.current_token_is(TokenType::Comma).is_ok() {
+                self.next_token()?;
+            }
+
+            items.push(self.parse_projection_item()?);
+            self.next_token()?; // moves to , or }
+        }
+        if items.is_empty() {
+            return Err(self.build_error(
+                ParseErrorReason::ExpectedToken((
+                    TokenType::RightBrace,
+                    Some(TokenType::Identifier),
+                )),
+                &self.current_token,
+            ));
+        }
+        Ok(Projection { items })
+    }
+    /// Parses an output column, optionally renamed with `as`.
+    fn parse_projection_item(&mut self) -> Result<ProjectionItem, ParseError> {
+        let expression = self.parse_expression()?;
+        let mut alias = None;
+        if self.peek_keyword_is("as").is_ok() {
+            self.next_token()?;
+            self.expect_peek(TokenType::Identifier)?;
+            alias = Some(self.parse_identifier()?);
+        }
+        Ok(ProjectionItem { expression, alias })
+    }
+    fn parse_aggregate(&mut self) -> Result<Aggregate, ParseError> {
+        let name = self.get_current_token()?.literal().clone();
+        let function = match name.as_str() {
+            "count" => AggregateFunction::Count,
+            "sum" => AggregateFunction::Sum,
+            "avg" => AggregateFunction::Avg,
+            "min" => AggregateFunction::Min,
+            "max" => AggregateFunction::Max,
+            _ => {
+                return Err(
+                    self.build_error(ParseErrorReason::UnknownFunction(name), &self.current_token)
+                );
+            }
+        };
+        self.expect_peek(TokenType::LeftParen)?;
+
+        // only count() may leave out the field
+        let mut field = None;
+        if function != AggregateFunction::Count
+            || self.peek_token_is(TokenType::RightParen).is_err()
+        {
+            self.expect_peek(TokenType::Identifier)?;
+            field = Some(self.parse_field()?);
+        }
+        self.expect_peek(TokenType::RightParen)?;
+
+        Ok(Aggregate { function, field })
+    }
+    fn parse_join(&mut self) -> Result<Join, ParseError> {
+        let mut kind = JoinKind::Inner;
+        if self.current_keyword_is("left").is_ok() {
+            kind = JoinKind::Left;
+            self.expect_peek_keyword("join")?;
+        } else if self.current_keyword_is("inner").is_ok() {
+            self.expect_peek_keyword("join")?;
+        }
+
+        self.expect_peek(TokenType::Identifier)?;
+        let table_identifier = self.parse_identifier()?;
+
+        // only equality is supported so the join can be done with a hash table
+        self.expect_peek_keyword("on")?;
+        self.expect_peek(TokenType::Identifier)?;
+        let left = self.parse_field()?;
+        self.expect_peek(TokenType::Equals)?;
+        self.expect_peek(TokenType::Identifier)?;
+        let right = self.parse_field()?;
+
+        Ok(Join {
+            kind,
+            table_identifier,
+            on: (left, right),
+        })
+    }
+    fn parse_group_by(&mut self) -> Result<GroupBy, ParseError> {
+        self.expect_peek_keyword("by")?;
+
+        let mut keys = Vec::new();
+        loop {
+            self.expect_peek(TokenType::Identifier)?;
+            keys.push(self.parse_field()?);
+            if self.peek_token_is(TokenType::Comma).is_err() {
+                break;
+            }
+            self.next_token()?;
+        }
+
+        let mut having = None;
+        if self.peek_keyword_is("having").is_ok() {
+            self.next_token()?;
+            self.next_token()?;
+            having = Some(self.parse_predicate()?);
+        }
+        Ok(GroupBy { keys, having })
+    }
+    fn parse_order_by(&mut self) -> Result<OrderBy, ParseError> {
+        self.expect_peek_keyword("by")?;
+
+        let mut keys = Vec::new();
+        loop {
+            self.expect_peek(TokenType::Identifier)?;
+            let field = self.parse_field()?;
+
+            let mut direction = SortDirection::Ascending;
+            if self.peek_keyword_is("asc").is_ok() {
+                self.next_token()?;
+            } else if self.peek_keyword_is("desc").is_ok() {
+                self.next_token()?;
+                direction = SortDirection::Descending;
+            }
+
+            // nulls sort as the largest value unless told otherwise
+            let mut nulls = match direction {
+                SortDirection::Ascending => NullsOrder::Last,
+                SortDirection::Descending => NullsOrder::First,
+            };
+            if self.peek_keyword_is("nulls").is_ok() {
+                self.next_token()?;
+                if self.peek_keyword_is("first").is_ok() {
+                    nulls = NullsOrder::First;
+                } else {
+                    self.peek_keyword_is("last")?;
+                    nulls = NullsOrder::Last;
+                }
+                self.next_token()?;
+            }
+
+            keys.push(OrderKey {
+                field,
+                direction,
+                nulls,
+            });
+
+            if self.peek_token_is(TokenType::Comma).is_err() {
+                break;
+            }
+            self.next_token()?;
+        }
+        Ok(OrderBy { keys })
+    }
     fn parse_limit(&mut self) -> Result<Limit, ParseError> {
         self.expect_peek(TokenType::Integer)?;
         let integer = self.parse_integer()?;
@@ -227,15 +625,211 @@ impl Parser<'_> {
             number: integer as usize,
         })
     }
-    fn parse_where(&mut self) -> Result<Where, ParseError> {
-        // identifier
+    fn parse_offset(&mut self) -> Result<Offset, ParseError> {
+        self.expect_peek(TokenType::Integer)?;
+        let integer = self.parse_integer()?;
+        Ok(Offset {
+            number: integer as usize,
+        })
+    }
+    fn parse_after(&mut self) -> Result<After, ParseError> {
         self.expect_peek(TokenType::Identifier)?;
-        let identifier = self.parse_identifier()?;
+        let cursor = self.parse_identifier()?;
+        Ok(After { cursor })
+    }
+    fn parse_where(&mut self) -> Result<Where, ParseError> {
+        self.next_token()?;
+        let predicate = self.parse_predicate()?;
+        Ok(Where { predicate })
+    }
+    fn parse_predicate(&mut self) -> Result<Predicate, ParseError> {
+        let first = self.parse_not_predicate()?;
+        self.parse_predicate_rest(first)
+    }
+    /// Continues a predicate whose first operand has already been parsed.
+    fn parse_predicate_rest(&mut self, first: Predicate) -> Result<Predicate, ParseError> {
+        let mut predicate = self.parse_and_predicate_rest(first)?;
+        while self.peek_token_is(TokenType::Or).is_ok() {
+            self.next_token()?;
+            self.next_token()?;
+            let right = self.parse_and_predicate()?;
+            predicate = Predicate::Or(Box::new(predicate), Box::new(right));
+        }
+        Ok(predicate)
+    }
+    fn parse_and_predicate(&mut self) -> Result<Predicate, ParseError> {
+        let first = self.parse_not_predicate()?;
+        self.parse_and_predicate_rest(first)
+    }
+    fn parse_and_predicate_rest(&mut self, first: Predicate) -> Result<Predicate, ParseError> {
+        let mut predicate = first;
+        while self.peek_token_is(TokenType::And).is_ok() {
+            self.next_token()?;
+            self.next_token()?;
+            let right = self.parse_not_predicate()?;
+            predicate = Predicate::And(Box::new(predicate), Box::new(right));
+        }
+        Ok(predicate)
+    }
+    fn parse_not_predicate(&mut self) -> Result<Predicate, ParseError> {
+        if self.current_token_is(TokenType::Not).is_ok() {
+            self.next_token()?;
+            let predicate = self.parse_not_predicate()?;
+            return Ok(Predicate::Not(Box::new(predicate)));
+        }
+        self.parse_primary_predicate()
+    }
+    fn parse_primary_predicate(&mut self) -> Result<Predicate, ParseError> {
+        if self.current_token_is(TokenType::LeftParen).is_ok() {
+            return match self.parse_parenthesized()? {
+                Parenthesized::Predicate(predicate) => Ok(predicate),
+                // the parentheses only grouped the start of an expression, as in `(a + b) * c > d`
+                Parenthesized::Expression(expression) => {
+                    let expression = self.parse_expression_rest(expression)?;
+                    self.next_token()?;
+                    self.parse_condition_rest(expression)
+                }
+            };
+        }
+        self.parse_condition()
+    }
+    /// Parses parentheses at the start of a condition, which either group a
+    /// predicate or the first operand of an expression. Which one is only known
+    /// once a comparison or the closing parenthesis is reached.
+    fn parse_parenthesized(&mut self) -> Result<Parenthesized, ParseError> {
+        self.next_token()?;
+        let group = if self.current_token_is(TokenType::Not).is_ok() {
+            Parenthesized::Predicate(self.parse_predicate()?)
+        } else {
+            let expression = match self.current_token_is(TokenType::LeftParen).is_ok() {
+                true => match self.parse_parenthesized()? {
+                    Parenthesized::Predicate(predicate) => {
+                        Parenthesized::Predicate(self.parse_predicate_rest(predicate)?)
+                    }
+                    Parenthesized::Expression(expression) => {
+                        Parenthesized::Expression(self.parse_expression_rest(expression)?)
+                    }
+                },
+                false => Parenthesized::Expression(self.parse_expression()?),
+            };
+            match expression {
+                Parenthesized::Expression(expression)
+                    if self.peek_token_is(TokenType::RightParen).is_err() =>
+                {
+                    self.next_token()?;
+                    let condition = self.parse_condition_rest(expression)?;
+                    Parenthesized::Predicate(self.parse_predicate_rest(condition)?)
+                }
+                group => group,
+            }
+        };
+        self.expect_peek(TokenType::RightParen)?;
+        Ok(group)
+    }
+    fn parse_condition(&mut self) -> Result<Predicate, ParseError> {
+        let expression = self.parse_expression()?;
         self.next_token()?;
+        self.parse_condition_rest(expression)
+    }
+    /// Parses what follows the left hand side of a condition, which has
+    /// already been parsed.
+    fn parse_condition_rest(&mut self, expression: Expression) -> Result<Predicate, ParseError> {
+        // `is null` / `is not null`
+        if self.current_keyword_is("is").is_ok() {
+            let field = self.expect_field(&expression)?;
+            let negated = self.peek_token_is(TokenType::Not).is_ok();
+            if negated {
+                self.next_token()?;
+            }
+            self.expect_peek(TokenType::Null)?;
+            return Ok(Predicate::IsNull(NullCondition { field, negated }));
+        }
+
+        // `in` / `not in`
+        let negated = self.current_token_is(TokenType::Not).is_ok();
+        if negated {
+            self.expect_peek_keyword("in")?;
+        }
+        if self.current_keyword_is("in").is_ok() {
+            return Ok(Predicate::In(InCondition {
+                field: self.expect_field(&expression)?,
+                values: self.parse_in_values()?,
+                negated,
+            }));
+        }
+
+        let (comparison, value) = self.parse_comparison()?;
+        Ok(Predicate::Condition(Condition {
+            expression,
+            comparison,
+            value,
+        }))
+    }
+    /// `is` and `in` only test plain fields, not computed values.
+    fn expect_field(&self, expression: &Expression) -> Result<Identifier, ParseError> {
+        match expression {
+            Expression::Field(field) => Ok(field.clone()),
+            _ => Err(self.build_error(
+                ParseErrorReason::ExpectedField(self.get_current_token()?.literal().clone()),
+                &self.current_token,
+            )),
+        }
+    }
+    fn parse_in_values(&mut self) -> Result<InValues, ParseError> {
+        self.expect_peek(TokenType::LeftParen)?;
+        if self.peek_token_is(TokenType::Gimme).is_ok() {
+            self.next_token()?;
+            let gimme = self.parse_gimme()?;
+            self.expect_peek(TokenType::RightParen)?;
+            return Ok(InValues::Query(Box::new(gimme)));
+        }
+
+        let mut values = Vec::new();
+        self.next_token()?;
+        while self.current_token_is(TokenType::RightParen).is_err() {
+            if !values.is_empty() {
+                self.expect_current(TokenType::Comma)?;
+            }
+            values.push(self.parse_data()?);
+            self.next_token()?; // moves to , or )
+        }
+        Ok(InValues::List(values))
+    }
+    /// Parses the comparison operator and the value on its right hand side.
+    fn parse_comparison(&mut self) -> Result<(Comparison, Data), ParseError> {
+        // regular expressions are compiled here so a bad pattern points at its literal
+        if self.current_token_is(TokenType::Tilde).is_ok() {
+            self.next_token()?;
+            let value = self.parse_data()?;
+            let Data::String(Some(pattern)) = &value else {
+                return Err(self.build_error(
+                    ParseErrorReason::ExpectedToken((
+                        self.get_current_token()?.token_type().clone(),
+                        Some(TokenType::String),
+                    )),
+                    &self.current_token,
+                ));
+            };
+            let pattern = Pattern::new(pattern).map_err(|e| {
+                // the caret drawn by the regex crate is replaced by our own highlight
+                let message = e.to_string();
+                let message = message.lines().last().unwrap_or_default();
+                let message = message.strip_prefix("error: ").unwrap_or(message);
+                self.build_error(
+                    ParseErrorReason::InvalidRegex(message.to_owned()),
+                    &self.current_token,
+                )
+            })?;
+            return Ok((Comparison::Regex(pattern), value));
+        }
 
         // comparison operator
         let comparison_token = self.get_current_token()?;
-        let comparison_operator = match Comparison::from_token_type(comparison_token.token_type()) {
+        let comparison_operator = match comparison_token.token_type() {
+            TokenType::Identifier => Comparison::from_keyword(comparison_token.literal()),
+            token_type => Comparison::from_token_type(token_type),
+        };
+        let comparison_operator = match comparison_operator {
             Some(v) => v,
             None => {
                 return Err(self.build_error(
@@ -244,31 +838,148 @@ impl Parser<'_> {
                 ));
             }
         };
+        if matches!(
+            comparison_operator,
+            Comparison::StartsWith { .. } | Comparison::EndsWith { .. }
+        ) {
+            self.expect_peek_keyword("with")?;
+        }
         self.next_token()?;
 
         // value
         let value = self.parse_data()?;
 
-        Ok(Where {
-            field: identifier,
-            comparison: comparison_operator,
-            value,
-        })
+        Ok((comparison_operator, value))
+    }
+
+    // EXPRESSIONS
+    fn parse_expression(&mut self) -> Result<Expression, ParseError> {
+        let first = self.parse_term()?;
+        self.parse_expression_rest(first)
+    }
+    /// Continues an expression whose first operand has already been parsed.
+    /// `+` and `-` bind loosest and everything is left associative.
+    fn parse_expression_rest(&mut self, first: Expression) -> Result<Expression, ParseError> {
+        let mut expression = self.parse_term_rest(first)?;
+        while let Some(operator) = self.peek_operator(Operator::Add.precedence()) {
+            self.next_token()?;
+            self.next_token()?;
+            let right = self.parse_term()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+        Ok(expression)
+    }
+    fn parse_term(&mut self) -> Result<Expression, ParseError> {
+        let first = self.parse_unary()?;
+        self.parse_term_rest(first)
+    }
+    fn parse_term_rest(&mut self, first: Expression) -> Result<Expression, ParseError> {
+        let mut expression = first;
+        while let Some(operator) = self.peek_operator(Operator::Multiply.precedence()) {
+            self.next_token()?;
+            self.next_token()?;
+            let right = self.parse_unary()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+        Ok(expression)
+    }
+    fn peek_operator(&self, precedence: u8) -> Option<Operator> {
+        self.peek_token
+            .as_ref()
+            .and_then(|token| Operator::from_token_type(token.token_type()))
+            .filter(|operator| operator.precedence() == precedence)
+    }
+    fn parse_unary(&mut self) -> Result<Expression, ParseError> {
+        if self.current_token_is(TokenType::Minus).is_err() {
+            return self.parse_operand();
+        }
+        // negative numbers stay literals so the smallest Int can be written
+        if self.peek_token_is(TokenType::Integer).is_ok()
+            || self.peek_token_is(TokenType::Float).is_ok()
+        {
+            return self.parse_data().map(Expression::Literal);
+        }
+        self.next_token()?;
+        let operand = self.parse_unary()?;
+        Ok(Expression::Negate(Box::new(operand)))
+    }
+    fn parse_operand(&mut self) -> Result<Expression, ParseError> {
+        let current_token = self.get_current_token()?;
+        match current_token.token_type() {
+            TokenType::LeftParen => {
+                self.next_token()?;
+                let expression = self.parse_expression()?;
+                self.expect_peek(TokenType::RightParen)?;
+                Ok(expression)
+            }
+            TokenType::Identifier if self.peek_token_is(TokenType::LeftParen).is_ok() => {
+                self.parse_aggregate().map(Expression::Aggregate)
+            }
+            TokenType::Identifier => self.parse_field().map(Expression::Field),
+            _ => self.parse_data().map(Expression::Literal),
+        }
     }
 
     // INSERT
     fn parse_insert(&mut self) -> Result<Insert, ParseError> {
-        self.expect_peek(TokenType::LeftBrace)?;
-        let values = self.parse_map()?;
+        self.next_token()?;
+        let source = match self.get_current_token()?.token_type().clone() {
+            TokenType::LeftBracket => InsertSource::Rows(self.parse_rows()?),
+            TokenType::Identifier if self.current_keyword_is("from").is_ok() => {
+                self.expect_peek(TokenType::LeftParen)?;
+                self.expect_peek(TokenType::Gimme)?;
+                let gimme = self.parse_gimme()?;
+                self.expect_peek(TokenType::RightParen)?;
+                InsertSource::Query(Box::new(gimme))
+            }
+            _ => InsertSource::Rows(vec![self.parse_map()?]),
+        };
 
-        self.expect_peek(TokenType::Into)?;
+        self.expect_peek_keyword("into")?;
 
         self.expect_peek(TokenType::Identifier)?;
         let table_identifier = self.parse_identifier()?;
 
         Ok(Insert {
-            values,
+            source,
+            table_identifier,
+        })
+    }
+    /// `[{..}, {..}]`, a non-empty list of rows with an optional trailing comma.
+    fn parse_rows(&mut self) -> Result<Vec<Map>, ParseError> {
+        let mut rows = Vec::new();
+        self.current_token_is(TokenType::LeftBracket)?;
+        loop {
+            self.expect_peek(TokenType::LeftBrace)?;
+            rows.push(self.parse_map()?);
+            if self.peek_token_is(TokenType::RightBracket).is_err() {
+                self.expect_peek(TokenType::Comma)?;
+            }
+            if self.peek_token_is(TokenType::RightBracket).is_ok() {
+                self.next_token()?;
+                return Ok(rows);
+            }
+        }
+    }
+
+    // UPDATE
+    fn parse_update(&mut self) -> Result<Update, ParseError> {
+        self.expect_peek(TokenType::Identifier)?;
+        let table_identifier = self.parse_identifier()?;
+
+        self.expect_peek_keyword("set")?;
+        self.expect_peek(TokenType::LeftBrace)?;
+        let values = self.parse_map()?;
+
+        let mut where_statement = None;
+        if self.peek_keyword_is("where").is_ok() {
+            self.next_token()?;
+            where_statement = Some(self.parse_where()?);
+        }
+        Ok(Update {
             table_identifier,
+            values,
+            where_statement,
         })
     }
 
@@ -277,20 +988,96 @@ impl Parser<'_> {
         Ok(Tables {})
     }
     fn parse_new_table(&mut self) -> Result<NewTable, ParseError> {
-        self.expect_peek(TokenType::Table)?;
+        self.expect_peek_keyword("table")?;
 
         self.expect_peek(TokenType::Identifier)?;
         let identifier = self.parse_identifier()?;
 
         self.expect_peek(TokenType::LeftBrace)?;
-        let fields = self.parse_map()?;
-        Ok(NewTable { identifier, fields })
+        let columns = self.parse_column_definitions()?;
+        Ok(NewTable {
+            identifier,
+            columns,
+        })
     }
     fn parse_delete_table(&mut self) -> Result<DeleteTable, ParseError> {
-        self.expect_peek(TokenType::Table)?;
+        self.expect_peek_keyword("table")?;
         self.expect_peek(TokenType::Identifier)?;
         let identifier = self.parse_identifier()?;
 
         Ok(DeleteTable { identifier })
     }
+    fn parse_alter_table(&mut self) -> Result<AlterTable, ParseError> {
+        self.expect_peek_keyword("table")?;
+        self.expect_peek(TokenType::Identifier)?;
+        let identifier = self.parse_identifier()?;
+
+        self.next_token()?;
+        let current_token = self.get_current_token()?;
+        let action = match current_token.token_type() {
+            TokenType::Identifier if self.current_keyword_is("add").is_ok() => {
+                self.expect_peek_keyword("column")?;
+                self.next_token()?;
+                let column = self.parse_column_definition()?;
+                let mut default = Data::Null;
+                if self.peek_keyword_is("default").is_ok() {
+                    self.next_token()?;
+                    self.next_token()?;
+                    default = self.parse_default()?;
+                }
+                AlterAction::AddColumn(column, default)
+            }
+            TokenType::Identifier if self.current_keyword_is("drop").is_ok() => {
+                self.expect_peek_keyword("column")?;
+                self.expect_peek(TokenType::Identifier)?;
+                AlterAction::DropColumn(self.parse_identifier()?)
+            }
+            TokenType::Identifier if self.current_keyword_is("rename").is_ok() => {
+                self.expect_peek_keyword("column")?;
+                self.expect_peek(TokenType::Identifier)?;
+                let from = self.parse_identifier()?;
+                self.expect_peek_keyword("to")?;
+                self.expect_peek(TokenType::Identifier)?;
+                AlterAction::RenameColumn(from, self.parse_identifier()?)
+            }
+            _ => {
+                return Err(self.build_error(
+                    ParseErrorReason::ExpectedAlterAction(current_token.literal().clone()),
+                    &self.current_token,
+                ));
+            }
+        };
+        Ok(AlterTable { identifier, action })
+    }
+    /// The value after `default`, where a bare `none` stands for no value.
+    fn parse_default(&mut self) -> Result<Data, ParseError> {
+        if self.current_keyword_is("none").is_ok() {
+            return Ok(Data::Null);
+        }
+        self.parse_data()
+    }
+    fn parse_remove(&mut self) -> Result<Remove, ParseError> {
+        let all = self.peek_keyword_is("all").is_ok();
+        if all {
+            self.next_token()?;
+        }
+        self.expect_peek_keyword("from")?;
+        self.expect_peek(TokenType::Identifier)?;
+        let table_identifier = self.parse_identifier()?;
+
+        let mut where_statement = None;
+        if !all {
+            if self.peek_keyword_is("where").is_err() {
+                return Err(
+                    self.build_error(ParseErrorReason::UnfilteredRemove, &self.current_token)
+                );
+            }
+            self.next_token()?;
+            where_statement = Some(self.parse_where()?);
+        }
+        Ok(Remove {
+            table_identifier,
+            where_statement,
+        })
+    }
 }
@@ -1,10 +1,15 @@
 use crate::{
     bql::{
-        ast::{Identifier, MapItem, Query},
+        ast::{
+            Aggregate, AggregateFunction, AlterAction, ColumnDefinition, Condition, Expression,
+            Identifier, InCondition, InValues, Insert, InsertSource, JoinKind, Map, MapItem,
+            NullCondition, NullsOrder, Operator, Predicate, ProjectionItem, Query, SortDirection,
+        },
         lexer::Lexer,
         parser::Parser,
+        token::LineColumn,
     },
-    database::data::{Comparison, Data},
+    database::data::{Comparison, Data, DataType},
 };
 
 #[test]
@@ -39,6 +44,22 @@ fn parse_gimme_limit() {
     }
 }
 
+#[test]
+fn parse_gimme_limit_offset_after() {
+    let input = "gimme users limit 50 offset 100 after zabcd;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            assert_eq!(gimme.limit_statement.unwrap().number, 50);
+            assert_eq!(gimme.offset_statement.unwrap().number, 100);
+            assert_eq!(gimme.after_statement.unwrap().cursor.value, "zabcd");
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
 #[test]
 fn parse_gimme_where_limit() {
     let input = "gimme users where age >= 18 limit 10;";
@@ -49,9 +70,16 @@ fn parse_gimme_where_limit() {
         Query::Gimme(gimme) => {
             assert_eq!(gimme.table_identifier.value, "users");
             let where_stmt = gimme.where_statement.expect("Expected where statement");
-            assert_eq!(where_stmt.field.value, "age");
-            assert_eq!(where_stmt.comparison, Comparison::GreaterEquals);
-            assert_eq!(where_stmt.value, Data::Int(Some(18)));
+            assert_eq!(
+                where_stmt.predicate,
+                Predicate::Condition(Condition {
+                    expression: Expression::Field(Identifier {
+                        value: "age".to_string(),
+                    }),
+                    comparison: Comparison::GreaterEquals,
+                    value: Data::Int(Some(18)),
+                })
+            );
             let limit_stmt = gimme.limit_statement.expect("Expected limit statement");
             assert_eq!(limit_stmt.number, 10);
         }
@@ -59,6 +87,583 @@ fn parse_gimme_where_limit() {
     }
 }
 
+#[test]
+fn parse_gimme_projection() {
+    let input = "gimme users {id, name} where age >= 18;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let projection = gimme.projection.expect("Expected projection");
+            let fields: Vec<String> = projection.items.iter().map(ProjectionItem::name).collect();
+            assert_eq!(fields, vec!["id", "name"]);
+            assert!(gimme.where_statement.is_some());
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_gimme_aggregates() {
+    let input = "gimme {count(), avg(age)} from Users where age > 30;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            assert_eq!(gimme.table_identifier.value, "Users");
+            let projection = gimme.projection.expect("Expected projection");
+            assert_eq!(
+                projection.items,
+                vec![
+                    ProjectionItem {
+                        expression: Expression::Aggregate(Aggregate {
+                            function: AggregateFunction::Count,
+                            field: None,
+                        }),
+                        alias: None,
+                    },
+                    ProjectionItem {
+                        expression: Expression::Aggregate(Aggregate {
+                            function: AggregateFunction::Avg,
+                            field: Some(Identifier {
+                                value: "age".to_string(),
+                            }),
+                        }),
+                        alias: None,
+                    },
+                ]
+            );
+            assert!(gimme.where_statement.is_some());
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_gimme_single_aggregate() {
+    let input = "gimme count() from Users where age > 30;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let projection = gimme.projection.expect("Expected projection");
+            match projection.items.as_slice() {
+                [
+                    ProjectionItem {
+                        expression: Expression::Aggregate(aggregate),
+                        alias: None,
+                    },
+                ] => {
+                    assert_eq!(aggregate.to_string(), "count()")
+                }
+                items => panic!("Expected a single aggregate, got {:?}", items),
+            }
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_gimme_group_by() {
+    let input = "gimme {team, count()} from Users group by team, country having count() > 5 and not team == red order by team limit 10;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let group_stmt = gimme.group_statement.expect("Expected group statement");
+            let keys: Vec<&str> = group_stmt
+                .keys
+                .iter()
+                .map(|key| key.value.as_str())
+                .collect();
+            assert_eq!(keys, vec!["team", "country"]);
+            assert_eq!(
+                group_stmt.having,
+                Some(Predicate::And(
+                    Box::new(Predicate::Condition(Condition {
+                        expression: Expression::Aggregate(Aggregate {
+                            function: AggregateFunction::Count,
+                            field: None,
+                        }),
+                        comparison: Comparison::Greater,
+                        value: Data::Int(Some(5)),
+                    })),
+                    Box::new(Predicate::Not(Box::new(Predicate::Condition(Condition {
+                        expression: Expression::Field(Identifier {
+                            value: "team".to_string(),
+                        }),
+                        comparison: Comparison::Equals,
+                        value: Data::String(Some("red".to_string())),
+                    })))),
+                ))
+            );
+            assert!(gimme.order_statement.is_some());
+            assert_eq!(gimme.limit_statement.expect("Expected limit").number, 10);
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_gimme_joins() {
+    let input = "gimme {Orders.id, Users.name} from Orders join Users on Orders.user_id == Users.id left join Teams on team_id == Teams.id where Users.age > 18;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let joins: Vec<(JoinKind, &str, &str, &str)> = gimme
+                .joins
+                .iter()
+                .map(|join| {
+                    (
+                        join.kind,
+                        join.table_identifier.value.as_str(),
+                        join.on.0.value.as_str(),
+                        join.on.1.value.as_str(),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                joins,
+                vec![
+                    (JoinKind::Inner, "Users", "Orders.user_id", "Users.id"),
+                    (JoinKind::Left, "Teams", "team_id", "Teams.id"),
+                ]
+            );
+            let projection = gimme.projection.expect("Expected projection");
+            assert_eq!(
+                projection.items[1],
+                ProjectionItem {
+                    expression: *field("Users.name"),
+                    alias: None,
+                }
+            );
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            assert_eq!(where_stmt.predicate.fields()[0].value, "Users.age");
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_in_conditions() {
+    let input = "gimme Orders where id in (1, -5, 9) and user_id not in (gimme {id} from Users where banned == true);";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            let Predicate::And(left, right) = where_stmt.predicate else {
+                panic!("Expected and predicate");
+            };
+            assert_eq!(
+                *left,
+                Predicate::In(InCondition {
+                    field: Identifier {
+                        value: "id".to_string(),
+                    },
+                    values: InValues::List(vec![
+                        Data::Int(Some(1)),
+                        Data::Int(Some(-5)),
+                        Data::Int(Some(9)),
+                    ]),
+                    negated: false,
+                })
+            );
+            match *right {
+                Predicate::In(InCondition {
+                    values: InValues::Query(subquery),
+                    negated: true,
+                    ..
+                }) => {
+                    assert_eq!(subquery.table_identifier.value, "Users");
+                    assert!(subquery.where_statement.is_some());
+                }
+                predicate => panic!("Expected not in subquery, got {:?}", predicate),
+            }
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_string_patterns() {
+    let input = "gimme Users where email like \"%@example.com\" or name istarts with al or name ends with son;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            let Predicate::Or(left, right) = where_stmt.predicate else {
+                panic!("Expected or predicate");
+            };
+            let Predicate::Or(like, starts) = *left else {
+                panic!("Expected or predicate");
+            };
+            let comparisons: Vec<Comparison> = [*like, *starts, *right]
+                .into_iter()
+                .map(|predicate| match predicate {
+                    Predicate::Condition(condition) => condition.comparison,
+                    predicate => panic!("Expected condition, got {:?}", predicate),
+                })
+                .collect();
+            assert_eq!(
+                comparisons,
+                vec![
+                    Comparison::Like { ignore_case: false },
+                    Comparison::StartsWith { ignore_case: true },
+                    Comparison::EndsWith { ignore_case: false },
+                ]
+            );
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+    let lexer = Lexer::new("gimme Users where name starts al;");
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_query().is_err());
+}
+
+#[test]
+fn parse_regex_condition() {
+    let input = "gimme Logs where path ~ \"^/api/v[0-9]+/\";";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            let Predicate::Condition(condition) = where_stmt.predicate else {
+                panic!("Expected condition");
+            };
+            assert_eq!(condition.comparison.to_string(), "~");
+            assert_eq!(
+                condition.value,
+                Data::String(Some("^/api/v[0-9]+/".to_string()))
+            );
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_invalid_regex() {
+    let input = "gimme Logs where path ~ \"^/api/(v1\";";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let err = parser.parse_query().unwrap_err();
+    let position = err.position().expect("Expected position");
+    assert_eq!(
+        &input[position.start_index..=position.end_index],
+        "\"^/api/(v1\""
+    );
+
+    let lexer = Lexer::new("gimme Logs where path ~ 5;");
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_query().is_err());
+}
+
+#[test]
+fn parse_invalid_in_conditions() {
+    for input in [
+        "gimme Users where id in 1;",
+        "gimme Users where id in (1 2);",
+        "gimme Users where id in (1,);",
+        "gimme Users where id not (1);",
+        "gimme Users where id in (gimme Users;",
+    ] {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_gimme_invalid_aggregates() {
+    for input in [
+        "gimme median(age) from Users;",
+        "gimme sum() from Users;",
+        "gimme {count(} from Users;",
+        "gimme {team} from Users group by;",
+        "gimme {team} from Users group by team having;",
+        "gimme Orders join Users;",
+        "gimme Orders join Users on user_id > Users.id;",
+        "gimme Orders left Users on user_id == Users.id;",
+        "gimme {Orders.} from Orders;",
+    ] {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_gimme_empty_projection() {
+    let input = "gimme users {};";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_query().is_err());
+}
+
+#[test]
+fn parse_gimme_order_by() {
+    let input = "gimme users order by age desc, name asc nulls first, id limit 10;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let order_stmt = gimme.order_statement.expect("Expected order statement");
+            let keys: Vec<(&str, SortDirection, NullsOrder)> = order_stmt
+                .keys
+                .iter()
+                .map(|key| (key.field.value.as_str(), key.direction, key.nulls))
+                .collect();
+            assert_eq!(
+                keys,
+                vec![
+                    ("age", SortDirection::Descending, NullsOrder::First),
+                    ("name", SortDirection::Ascending, NullsOrder::First),
+                    ("id", SortDirection::Ascending, NullsOrder::Last),
+                ]
+            );
+            assert_eq!(gimme.limit_statement.unwrap().number, 10);
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+fn condition(field: &str, comparison: Comparison, value: Data) -> Box<Predicate> {
+    Box::new(Predicate::Condition(Condition {
+        expression: Expression::Field(Identifier {
+            value: field.to_string(),
+        }),
+        comparison,
+        value,
+    }))
+}
+
+#[test]
+fn parse_gimme_where_boolean() {
+    let input = "gimme users where age >= 18 and (name == Alice or not name == Tom) limit 10;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            let expected = Predicate::And(
+                condition("age", Comparison::GreaterEquals, Data::Int(Some(18))),
+                Box::new(Predicate::Or(
+                    condition(
+                        "name",
+                        Comparison::Equals,
+                        Data::String(Some("Alice".to_string())),
+                    ),
+                    Box::new(Predicate::Not(condition(
+                        "name",
+                        Comparison::Equals,
+                        Data::String(Some("Tom".to_string())),
+                    ))),
+                )),
+            );
+            assert_eq!(where_stmt.predicate, expected);
+            assert!(gimme.limit_statement.is_some());
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_where_and_binds_tighter_than_or() {
+    let input = "gimme users where a == 1 or b == 2 and c == 3;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            let expected = Predicate::Or(
+                condition("a", Comparison::Equals, Data::Int(Some(1))),
+                Box::new(Predicate::And(
+                    condition("b", Comparison::Equals, Data::Int(Some(2))),
+                    condition("c", Comparison::Equals, Data::Int(Some(3))),
+                )),
+            );
+            assert_eq!(where_stmt.predicate, expected);
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+fn field(name: &str) -> Box<Expression> {
+    Box::new(Expression::Field(Identifier {
+        value: name.to_string(),
+    }))
+}
+
+fn binary(left: Box<Expression>, operator: Operator, right: Box<Expression>) -> Box<Expression> {
+    Box::new(Expression::Binary(left, operator, right))
+}
+
+fn parse_where(input: &str) -> Predicate {
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    match parser.parse_query().unwrap() {
+        Query::Gimme(gimme) => gimme.where_statement.unwrap().predicate,
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_arithmetic_precedence() {
+    let predicate = parse_where("gimme Orders where price * quantity - -discount % 2 > 100;");
+    let expected = binary(
+        binary(field("price"), Operator::Multiply, field("quantity")),
+        Operator::Subtract,
+        binary(
+            Box::new(Expression::Negate(field("discount"))),
+            Operator::Remainder,
+            Box::new(Expression::Literal(Data::Int(Some(2)))),
+        ),
+    );
+    assert_eq!(
+        predicate,
+        Predicate::Condition(Condition {
+            expression: *expected,
+            comparison: Comparison::Greater,
+            value: Data::Int(Some(100)),
+        })
+    );
+}
+
+#[test]
+fn parse_parenthesized_expressions() {
+    // leading parentheses may group arithmetic or a predicate
+    let sum = binary(field("a"), Operator::Add, field("b"));
+    let grouped = Predicate::Condition(Condition {
+        expression: *binary(
+            sum.clone(),
+            Operator::Multiply,
+            Box::new(Expression::Literal(Data::Int(Some(2)))),
+        ),
+        comparison: Comparison::Greater,
+        value: Data::Int(Some(5)),
+    });
+    assert_eq!(parse_where("gimme t where (a + b) * 2 > 5;"), grouped);
+    assert_eq!(parse_where("gimme t where ((a + b) * 2 > 5);"), grouped);
+
+    let condition_on_sum = Predicate::Condition(Condition {
+        expression: *sum,
+        comparison: Comparison::Equals,
+        value: Data::Int(Some(1)),
+    });
+    assert_eq!(
+        parse_where("gimme t where ((a + b) == 1 or c == 2) and d == 3;"),
+        Predicate::And(
+            Box::new(Predicate::Or(
+                Box::new(condition_on_sum),
+                condition("c", Comparison::Equals, Data::Int(Some(2))),
+            )),
+            condition("d", Comparison::Equals, Data::Int(Some(3))),
+        )
+    );
+}
+
+#[test]
+fn parse_computed_projection() {
+    let input = "gimme {name, age + 1 as next_age, -age, age as years, count() as total, sum(age) / count()} from Users;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let Query::Gimme(gimme) = parser.parse_query().unwrap() else {
+        panic!("Expected Gimme query");
+    };
+    let items = gimme.projection.unwrap().items;
+    let names: Vec<String> = items.iter().map(ProjectionItem::name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "name",
+            "next_age",
+            "-age",
+            "years",
+            "total",
+            "sum(age) / count()"
+        ]
+    );
+    assert_eq!(
+        items[1],
+        ProjectionItem {
+            expression: *binary(
+                field("age"),
+                Operator::Add,
+                Box::new(Expression::Literal(Data::Int(Some(1)))),
+            ),
+            alias: Some(Identifier {
+                value: "next_age".to_string(),
+            }),
+        }
+    );
+}
+
+#[test]
+fn parse_distinct() {
+    for (input, distinct) in [
+        ("gimme distinct {country} from Users;", true),
+        ("gimme distinct Users {country, city};", true),
+        ("gimme distinct count() from Users;", true),
+        ("gimme Users {country};", false),
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        let Query::Gimme(gimme) = parser.parse_query().unwrap() else {
+            panic!("Expected Gimme query");
+        };
+        assert_eq!(gimme.distinct, distinct, "{}", input);
+        assert_eq!(gimme.table_identifier.value, "Users");
+        assert!(gimme.projection.is_some());
+    }
+    let mut parser = Parser::new(Lexer::new("gimme distinct;")).unwrap();
+    assert!(parser.parse_query().is_err());
+}
+
+#[test]
+fn parse_invalid_expressions() {
+    let inputs = vec![
+        "gimme t where a + > 1;",
+        "gimme t where (a + 1 > 2;",
+        "gimme t where a + 1 in (1, 2);",
+        "gimme t where a * 2 is null;",
+        "gimme {a +} from t;",
+        "gimme {a as} from t;",
+    ];
+    for input in inputs {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_where_unclosed_paren() {
+    let input = "gimme users where (a == 1 or b == 2;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_query().is_err());
+}
+
+fn rows(insert: &Insert) -> &Vec<Map> {
+    match &insert.source {
+        InsertSource::Rows(rows) => rows,
+        InsertSource::Query(_) => panic!("Expected rows to insert"),
+    }
+}
+
 #[test]
 fn parse_insert_simple() {
     let input = "insert {id: 1, name: John, wealth: 1.5, dead: false} into users;";
@@ -68,21 +673,500 @@ fn parse_insert_simple() {
     match query {
         Query::Insert(insert) => {
             assert_eq!(insert.table_identifier.value, "users");
-            assert_eq!(insert.values.len(), 4);
-            assert_eq!(insert.values[0].key.value, "id");
-            assert_eq!(insert.values[0].value, Data::Int(Some(1)));
-            assert_eq!(insert.values[1].key.value, "name");
+            assert_eq!(rows(&insert)[0].len(), 4);
+            assert_eq!(rows(&insert)[0][0].key.value, "id");
+            assert_eq!(rows(&insert)[0][0].value, Data::Int(Some(1)));
+            assert_eq!(rows(&insert)[0][1].key.value, "name");
             assert_eq!(
-                insert.values[1].value,
+                rows(&insert)[0][1].value,
                 Data::String(Some("John".to_string()))
             );
-            assert_eq!(insert.values[2].key.value, "wealth");
-            assert_eq!(insert.values[2].value, Data::Float(Some(1.5)));
-            assert_eq!(insert.values[3].key.value, "dead");
-            assert_eq!(insert.values[3].value, Data::Boolean(Some(false)));
+            assert_eq!(rows(&insert)[0][2].key.value, "wealth");
+            assert_eq!(rows(&insert)[0][2].value, Data::Float(Some(1.5)));
+            assert_eq!(rows(&insert)[0][3].key.value, "dead");
+            assert_eq!(rows(&insert)[0][3].value, Data::Boolean(Some(false)));
+        }
+        _ => panic!("Expected Insert query"),
+    }
+}
+
+#[test]
+fn parse_insert_batch() {
+    for input in [
+        "insert [{id: 1, name: John}, {id: 2, name: null}] into users;",
+        "insert [\n  {id: 1, name: John},\n  {id: 2, name: null},\n] into users;",
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        let Query::Insert(insert) = parser.parse_query().unwrap() else {
+            panic!("Expected Insert query");
+        };
+        assert_eq!(insert.table_identifier.value, "users");
+        assert_eq!(rows(&insert).len(), 2);
+        assert_eq!(rows(&insert)[1][0].value, Data::Int(Some(2)));
+        assert_eq!(rows(&insert)[1][1].value, Data::Null);
+    }
+    for input in [
+        "insert [] into users;",
+        "insert [{id: 1} {id: 2}] into users;",
+        "insert [{id: 1}, into users;",
+        "insert [{id: 1}] users;",
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_insert_from() {
+    let input = "insert from (gimme {id, total * 2 as total} from Orders where year < 2024) into ArchivedOrders;";
+    let mut parser = Parser::new(Lexer::new(input)).unwrap();
+    let Query::Insert(insert) = parser.parse_query().unwrap() else {
+        panic!("Expected Insert query");
+    };
+    assert_eq!(insert.table_identifier.value, "ArchivedOrders");
+    let InsertSource::Query(gimme) = insert.source else {
+        panic!("Expected a query to insert from");
+    };
+    assert_eq!(gimme.table_identifier.value, "Orders");
+    assert_eq!(gimme.projection.unwrap().items.len(), 2);
+    assert!(gimme.where_statement.is_some());
+    assert!(gimme.limit_statement.is_none());
+
+    for input in [
+        "insert from gimme Orders into ArchivedOrders;",
+        "insert from (gimme Orders into ArchivedOrders;",
+        "insert from (Orders) into ArchivedOrders;",
+        "insert from (gimme Orders);",
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_alter_table() {
+    let column = |name: &str, datatype: DataType, nullable: bool| ColumnDefinition {
+        name: Identifier {
+            value: name.to_string(),
+        },
+        datatype,
+        nullable,
+    };
+    let identifier = |value: &str| Identifier {
+        value: value.to_string(),
+    };
+    for (input, action) in [
+        (
+            "alter table Users add column email: String default none;",
+            AlterAction::AddColumn(column("email", DataType::String, true), Data::Null),
+        ),
+        (
+            "alter table Users add column email: String default \"none\";",
+            AlterAction::AddColumn(
+                column("email", DataType::String, true),
+                Data::String(Some("none".to_string())),
+            ),
+        ),
+        (
+            "alter table Users add column score: Int not null default -1;",
+            AlterAction::AddColumn(column("score", DataType::Int, false), Data::Int(Some(-1))),
+        ),
+        (
+            "alter table Users add column active: Boolean;",
+            AlterAction::AddColumn(column("active", DataType::Boolean, true), Data::Null),
+        ),
+        (
+            "alter table Users drop column email;",
+            AlterAction::DropColumn(identifier("email")),
+        ),
+        (
+            "alter table Users rename column age to years;",
+            AlterAction::RenameColumn(identifier("age"), identifier("years")),
+        ),
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        let Query::AlterTable(alter_table) = parser.parse_query().unwrap() else {
+            panic!("Expected AlterTable query");
+        };
+        assert_eq!(alter_table.identifier.value, "Users");
+        assert_eq!(alter_table.action, action, "{}", input);
+    }
+    for input in [
+        "alter table Users;",
+        "alter Users drop column email;",
+        "alter table Users add email: String;",
+        "alter table Users add column email: String default;",
+        "alter table Users drop email;",
+        "alter table Users rename column age years;",
+        "alter table Users modify column age: Float;",
+    ] {
+        let mut parser = Parser::new(Lexer::new(input)).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_update() {
+    let input = "update users set {name: Bob, age: 31} where id == 2;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Update(update) => {
+            assert_eq!(update.table_identifier.value, "users");
+            assert_eq!(update.values.len(), 2);
+            assert_eq!(update.values[1].key.value, "age");
+            assert_eq!(update.values[1].value, Data::Int(Some(31)));
+            assert!(update.where_statement.is_some());
+        }
+        _ => panic!("Expected Update query"),
+    }
+}
+
+#[test]
+fn parse_remove() {
+    let inputs = vec![
+        ("remove from users where id == 2;", true),
+        ("remove all from users;", false),
+    ];
+    for (input, has_where) in inputs {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        let query = parser.parse_query().unwrap();
+        match query {
+            Query::Remove(remove) => {
+                assert_eq!(remove.table_identifier.value, "users");
+                assert_eq!(remove.where_statement.is_some(), has_where);
+            }
+            _ => panic!("Expected Remove query"),
+        }
+    }
+}
+
+#[test]
+fn parse_remove_requires_where_or_all() {
+    let inputs = vec!["remove from users;", "remove all from users where id == 2;"];
+    for input in inputs {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err());
+    }
+}
+
+#[test]
+fn parse_insert_quoted_string() {
+    let input = r#"insert {name: "Thomas Forbes", email: 'tom@example.com'} into users;"#;
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Insert(insert) => {
+            assert_eq!(
+                rows(&insert)[0][0].value,
+                Data::String(Some("Thomas Forbes".to_string()))
+            );
+            assert_eq!(
+                rows(&insert)[0][1].value,
+                Data::String(Some("tom@example.com".to_string()))
+            );
+        }
+        _ => panic!("Expected Insert query"),
+    }
+}
+
+#[test]
+fn parse_numeric_values() {
+    let input = "insert {a: -100, b: -2.5, c: 0xFF, d: 1_000_000, e: 1e3, f: -0x10, g: -9223372036854775808} into t;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Insert(insert) => {
+            let values: Vec<Data> = rows(&insert)[0]
+                .iter()
+                .map(|item| item.value.clone())
+                .collect();
+            assert_eq!(
+                values,
+                vec![
+                    Data::Int(Some(-100)),
+                    Data::Float(Some(-2.5)),
+                    Data::Int(Some(255)),
+                    Data::Int(Some(1_000_000)),
+                    Data::Float(Some(1000.0)),
+                    Data::Int(Some(-16)),
+                    Data::Int(Some(i64::MIN)),
+                ]
+            );
+        }
+        _ => panic!("Expected Insert query"),
+    }
+}
+
+#[test]
+fn parse_negative_where() {
+    let input = "gimme accounts where balance < -100;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            let where_stmt = gimme.where_statement.expect("Expected where statement");
+            assert_eq!(
+                where_stmt.predicate,
+                *condition("balance", Comparison::Less, Data::Int(Some(-100)))
+            );
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+#[test]
+fn parse_integer_out_of_range() {
+    let inputs = vec![
+        "insert {a: 9223372036854775808} into t;",
+        "insert {a: 0x1_0000_0000_0000_0000} into t;",
+        "insert {a: -hello} into t;",
+    ];
+    for input in inputs {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err());
+    }
+}
+
+#[test]
+fn parse_keyword_column_names() {
+    let input = "new table order_items {`table`: String, `limit`: Int, qty2: Int};";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::NewTable(new_table) => {
+            assert_eq!(new_table.identifier.value, "order_items");
+            let keys: Vec<&str> = new_table
+                .columns
+                .iter()
+                .map(|column| column.name.value.as_str())
+                .collect();
+            assert_eq!(keys, vec!["table", "limit", "qty2"]);
+        }
+        _ => panic!("Expected NewTable query"),
+    }
+}
+
+#[test]
+fn parse_contextual_keywords() {
+    let input = "gimme {first, last} from order where status == all order by first asc nulls last, last desc offset 1;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Gimme(gimme) => {
+            assert_eq!(gimme.table_identifier.value, "order");
+            assert_eq!(
+                gimme.where_statement.unwrap().predicate,
+                *condition(
+                    "status",
+                    Comparison::Equals,
+                    Data::String(Some("all".to_string())),
+                )
+            );
+            let order_stmt = gimme.order_statement.expect("Expected order statement");
+            let keys: Vec<(&str, SortDirection, NullsOrder)> = order_stmt
+                .keys
+                .iter()
+                .map(|key| (key.field.value.as_str(), key.direction, key.nulls))
+                .collect();
+            assert_eq!(
+                keys,
+                vec![
+                    ("first", SortDirection::Ascending, NullsOrder::Last),
+                    ("last", SortDirection::Descending, NullsOrder::First),
+                ]
+            );
+            assert!(gimme.offset_statement.is_some());
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+
+    let input = "new table to {from: Int, default: String, column: Boolean};";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(matches!(parser.parse_query().unwrap(), Query::NewTable(_)));
+
+    let input = "alter table to rename column from to default;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    match parser.parse_query().unwrap() {
+        Query::AlterTable(alter_table) => match alter_table.action {
+            AlterAction::RenameColumn(from, to) => {
+                assert_eq!(
+                    (from.value.as_str(), to.value.as_str()),
+                    ("from", "default")
+                );
+            }
+            _ => panic!("Expected RenameColumn"),
+        },
+        _ => panic!("Expected AlterTable query"),
+    }
+
+    let input = "gimme T left join U on T.id == U.id where side == left limit 1;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    match parser.parse_query().unwrap() {
+        Query::Gimme(gimme) => {
+            assert_eq!(gimme.joins[0].kind, JoinKind::Left);
+            assert_eq!(
+                gimme.where_statement.unwrap().predicate,
+                *condition(
+                    "side",
+                    Comparison::Equals,
+                    Data::String(Some("left".to_string())),
+                )
+            );
+        }
+        _ => panic!("Expected Gimme query"),
+    }
+
+    let input = "insert {side: left, join: inner, group: as} into table;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    match parser.parse_query().unwrap() {
+        Query::Insert(insert) => {
+            assert_eq!(insert.table_identifier.value, "table");
+            let values: Vec<(&str, &Data)> = rows(&insert)[0]
+                .iter()
+                .map(|item| (item.key.value.as_str(), &item.value))
+                .collect();
+            assert_eq!(
+                values,
+                vec![
+                    ("side", &Data::String(Some("left".to_string()))),
+                    ("join", &Data::String(Some("inner".to_string()))),
+                    ("group", &Data::String(Some("as".to_string()))),
+                ]
+            );
         }
         _ => panic!("Expected Insert query"),
     }
+
+    // comparison words are only operators right after the left hand side
+    let predicate = parse_where("gimme T where like contains with and in ends with like;");
+    let expected = Predicate::And(
+        condition(
+            "like",
+            Comparison::Contains { ignore_case: false },
+            Data::String(Some("with".to_string())),
+        ),
+        condition(
+            "in",
+            Comparison::EndsWith { ignore_case: false },
+            Data::String(Some("like".to_string())),
+        ),
+    );
+    assert_eq!(predicate, expected);
+    let predicate = parse_where("gimme T where is is not null or with not in (in, is);");
+    assert!(matches!(
+        predicate,
+        Predicate::Or(left, right)
+            if matches!(*left, Predicate::IsNull(NullCondition { negated: true, .. }))
+                && matches!(*right, Predicate::In(InCondition { negated: true, .. }))
+    ));
+
+    let input = "update set set {set: set} where set == set;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    match parser.parse_query().unwrap() {
+        Query::Update(update) => {
+            assert_eq!(update.table_identifier.value, "set");
+            assert_eq!(update.values[0].key.value, "set");
+            assert_eq!(
+                update.values[0].value,
+                Data::String(Some("set".to_string()))
+            );
+            assert!(update.where_statement.is_some());
+        }
+        _ => panic!("Expected Update query"),
+    }
+
+    let err = Parser::new(Lexer::new("gimme users order id;"))
+        .unwrap()
+        .parse_query()
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Received `id` but expected `by`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn parse_script() {
+    let input = "// seed\nnew table users {\n    id: Int,\n    name: String\n};\ninsert {id: 1, name: Tom} into users; gimme users;\n";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let statements = parser.parse_script().unwrap();
+    assert_eq!(statements.len(), 3);
+
+    assert!(matches!(statements[0].query, Query::NewTable(_)));
+    assert_eq!(statements[0].start, LineColumn { line: 2, column: 1 });
+    assert_eq!(statements[0].end, LineColumn { line: 5, column: 2 });
+
+    assert!(matches!(statements[1].query, Query::Insert(_)));
+    assert_eq!(statements[1].start, LineColumn { line: 6, column: 1 });
+
+    assert!(matches!(statements[2].query, Query::Gimme(_)));
+    assert_eq!(
+        statements[2].start,
+        LineColumn {
+            line: 6,
+            column: 39
+        }
+    );
+    let position = &statements[2].position;
+    assert_eq!(
+        &input[position.start_index..=position.end_index],
+        "gimme users;"
+    );
+}
+
+#[test]
+fn parse_script_empty() {
+    let lexer = Lexer::new("// nothing to see here\n/* or here */");
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_script().unwrap().is_empty());
+}
+
+#[test]
+fn parse_script_error_line() {
+    let input = "gimme users;\ngimme users where;\ngimme users;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let err = parser.parse_script().unwrap_err();
+    assert!(err.to_string().contains("2:18"));
+}
+
+#[test]
+fn parse_statement_before_error() {
+    let input = "gimme users;\ngimme users where;\ngimme users;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let statement = parser.parse_statement().unwrap().unwrap();
+    assert!(matches!(statement.query, Query::Gimme(_)));
+    assert_eq!(statement.start, LineColumn { line: 1, column: 1 });
+    assert!(parser.parse_statement().is_err());
+}
+
+#[test]
+fn parse_statement_before_lexer_error() {
+    let input = "gimme users;\n@ gimme users;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let statement = parser.parse_statement().unwrap().unwrap();
+    assert!(matches!(statement.query, Query::Gimme(_)));
+    let err = parser.parse_statement().unwrap_err();
+    assert_eq!(err.position().unwrap().start_index, 13);
 }
 
 #[test]
@@ -130,38 +1214,75 @@ fn parse_value_map() {
 }
 
 #[test]
-fn parse_type_map() {
-    let input = "{id: Int, name: String, wealth: Float, dead: Boolean};";
+fn parse_column_definitions() {
+    let input =
+        "new table users {id: Int not null, name: String, wealth: Float null, dead: Boolean};";
     let lexer = Lexer::new(input);
     let mut parser = Parser::new(lexer).unwrap();
-    let map = parser.parse_map().unwrap();
-    let expected_map = vec![
-        MapItem {
-            key: Identifier {
-                value: "id".to_string(),
-            },
-            value: Data::Int(None),
-        },
-        MapItem {
-            key: Identifier {
-                value: "name".to_string(),
-            },
-            value: Data::String(None),
-        },
-        MapItem {
-            key: Identifier {
-                value: "wealth".to_string(),
-            },
-            value: Data::Float(None),
-        },
-        MapItem {
-            key: Identifier {
-                value: "dead".to_string(),
-            },
-            value: Data::Boolean(None),
+    let query = parser.parse_query().unwrap();
+    let column = |name: &str, datatype, nullable| ColumnDefinition {
+        name: Identifier {
+            value: name.to_string(),
         },
-    ];
-    assert_eq!(map, expected_map);
+        datatype,
+        nullable,
+    };
+    match query {
+        Query::NewTable(new_table) => assert_eq!(
+            new_table.columns,
+            vec![
+                column("id", DataType::Int, false),
+                column("name", DataType::String, true),
+                column("wealth", DataType::Float, true),
+                column("dead", DataType::Boolean, true),
+            ]
+        ),
+        _ => panic!("Expected NewTable query"),
+    }
+}
+
+#[test]
+fn parse_invalid_column_definitions() {
+    for input in [
+        "new table users {id: 5};",
+        "new table users {id: Int not};",
+        "new table users {id};",
+    ] {
+        let lexer = Lexer::new(input);
+        let mut parser = Parser::new(lexer).unwrap();
+        assert!(parser.parse_query().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn parse_null() {
+    let input = "update users set {age: null} where name is not null and age is null;";
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer).unwrap();
+    let query = parser.parse_query().unwrap();
+    match query {
+        Query::Update(update) => {
+            assert_eq!(update.values[0].value, Data::Null);
+            let where_stmt = update.where_statement.expect("Expected where statement");
+            let null_condition = |field: &str, negated| {
+                Box::new(Predicate::IsNull(NullCondition {
+                    field: Identifier {
+                        value: field.to_string(),
+                    },
+                    negated,
+                }))
+            };
+            assert_eq!(
+                where_stmt.predicate,
+                Predicate::And(null_condition("name", true), null_condition("age", false))
+            );
+        }
+        _ => panic!("Expected Update query"),
+    }
+    // type names are no longer values
+    let lexer = Lexer::new("insert {age: Int} into users;");
+    let mut parser = Parser::new(lexer).unwrap();
+    assert!(parser.parse_query().is_err());
 }
 
 #[test]
@@ -172,6 +1293,8 @@ fn semicolon_required() {
         "tables",
         "new table users {id: Int, name: String, wealth: Float, dead: Boolean}",
         "delete table users",
+        "update users set {age: 1}",
+        "remove all from users",
     ];
     for input in inputs {
         let lexer = Lexer::new(input);
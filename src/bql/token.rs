@@ -1,19 +1,22 @@
+use std::fmt;
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum TokenType {
     // keywords
 
     // gimme
     Gimme,
-    Where,
-    Limit,
     // insert
     Insert,
-    Into,
+    // update
+    Update,
+    // remove
+    Remove,
     // tables
     Tables,
-    Table,
     New,
     Delete,
+    Alter,
     // data types
     IntWord,
     StringWord,
@@ -22,6 +25,11 @@ pub enum TokenType {
     // boolean
     True,
     False,
+    Null,
+    // logical
+    And,
+    Or,
+    Not,
 
     // comparison
     Equals,
@@ -30,18 +38,32 @@ pub enum TokenType {
     LessEquals,
     Greater,
     GreaterEquals,
+    Tilde,
+
+    // arithmetic
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
 
     // data
     Identifier,
     Integer,
     Float,
+    String,
 
     // delimiters
     LeftBrace,
     RightBrace,
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
     Semicolon,
     Colon,
     Comma,
+    Dot,
 }
 
 #[derive(Clone, Debug)]
@@ -50,6 +72,30 @@ pub struct TokenPosition {
     pub end_index: usize,
 }
 
+/// 1-based line and column, for pointing at a spot in multi-line input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn from_index(input: &str, index: usize) -> Self {
+        let before = &input[..index.min(input.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Token {
     token_type: TokenType,
@@ -64,14 +110,14 @@ impl Token {
         start_index: usize,
         end_index: usize,
     ) -> Self {
-        return Token {
+        Token {
             token_type,
             literal,
             position: TokenPosition {
                 start_index,
                 end_index,
             },
-        };
+        }
     }
     pub fn literal(&self) -> &String {
         &self.literal
@@ -91,20 +137,25 @@ pub fn lookup_identifier(identifier: &str) -> TokenType {
     }
 }
 
+/// Only words that start a statement, name a type or a literal, or combine
+/// conditions are reserved. Clause words like `where` or `like` lex as
+/// identifiers and are matched by position in the parser, so they still work as
+/// names and bare values.
 fn keyword_to_token(keyword: &str) -> Option<TokenType> {
     match keyword {
         // gimme
         "gimme" => Some(TokenType::Gimme),
-        "where" => Some(TokenType::Where),
-        "limit" => Some(TokenType::Limit),
         // insert
         "insert" => Some(TokenType::Insert),
-        "into" => Some(TokenType::Into),
+        // update
+        "update" => Some(TokenType::Update),
+        // remove
+        "remove" => Some(TokenType::Remove),
         // tables
         "tables" => Some(TokenType::Tables),
-        "table" => Some(TokenType::Table),
         "new" => Some(TokenType::New),
         "delete" => Some(TokenType::Delete),
+        "alter" => Some(TokenType::Alter),
         // data types
         "Int" => Some(TokenType::IntWord),
         "Float" => Some(TokenType::FloatWord),
@@ -113,6 +164,11 @@ fn keyword_to_token(keyword: &str) -> Option<TokenType> {
         // boolean
         "true" => Some(TokenType::True),
         "false" => Some(TokenType::False),
+        "null" => Some(TokenType::Null),
+        // logical
+        "and" => Some(TokenType::And),
+        "or" => Some(TokenType::Or),
+        "not" => Some(TokenType::Not),
         _ => None,
     }
 }
@@ -1,12 +1,18 @@
 use crate::{
-    bql::{lexer::Lexer, parser::Parser},
+    bql::{ast, lexer::Lexer, parser::Parser},
     database::query::Engine,
 };
 
+mod aggregate;
+mod cursor;
 pub mod data;
 mod query;
 mod storage;
 mod table;
+#[cfg(test)]
+mod tests;
+
+pub use table::{Cell, ResultColumn, Row, Rows};
 
 pub struct Database {
     file: storage::File,
@@ -17,16 +23,37 @@ impl Database {
         let file = storage::File::open(file_path);
         Database { file }
     }
-    pub fn handle_query(&mut self, query: &str) -> Result<String, String> {
-        let lexer = Lexer::new(&query);
-
-        let mut parser = Parser::new(lexer).map_err(|err| err.to_string())?;
-        let query = parser.parse_query().map_err(|err| err.to_string())?;
+    /// Runs every statement in `input` in order, stopping at the first one that
+    /// fails. Nothing runs if any of them doesn't parse.
+    pub fn handle_query(&mut self, input: &str) -> Result<String, String> {
+        let mut outputs = Vec::new();
+        for query in Database::parse(input)? {
+            outputs.push(self.execute(query)?);
+        }
+        Ok(outputs.join("\n"))
+    }
+    /// Runs a `gimme` and returns its rows along with the name and type of
+    /// every column, for callers that want the data rather than a printed table.
+    pub fn query(&mut self, input: &str) -> Result<Rows, String> {
+        let [query] = <[ast::Query; 1]>::try_from(Database::parse(input)?)
+            .map_err(|_| "Expected exactly one statement".to_string())?;
 
-        let mut engine = Engine::new(&mut self.file);
+        let mut engine = Engine::new(&mut self.file).map_err(|err| err.to_string())?;
+        engine.query(query).map_err(|err| err.to_string())
+    }
+    pub fn execute(&mut self, query: ast::Query) -> Result<String, String> {
+        let mut engine = Engine::new(&mut self.file).map_err(|err| err.to_string())?;
         engine.handle_query(query).map_err(|err| err.to_string())
     }
     pub fn delete(&mut self) -> Result<(), std::io::Error> {
         self.file.delete()
     }
+    fn parse(input: &str) -> Result<Vec<ast::Query>, String> {
+        let mut parser = Parser::new(Lexer::new(input)).map_err(|err| err.to_string())?;
+        let statements = parser.parse_script().map_err(|err| err.to_string())?;
+        Ok(statements
+            .into_iter()
+            .map(|statement| statement.query)
+            .collect())
+    }
 }
@@ -0,0 +1,173 @@
+use std::cmp::Ordering;
+
+use crate::{
+    bql::ast::{Aggregate, AggregateFunction},
+    database::{
+        data::{Data, DataType},
+        table::TableError,
+    },
+};
+
+/// Running state of one aggregate over the rows of a result (or of a group).
+#[derive(Debug, Clone)]
+pub enum Accumulator {
+    Count(i64),
+    Sum(Option<Data>),
+    /// Ints are summed exactly, a column only ever fills one of the sums.
+    Avg {
+        ints: i128,
+        floats: f64,
+        count: i64,
+    },
+    Min(Option<Data>),
+    Max(Option<Data>),
+}
+
+impl Accumulator {
+    pub fn new(function: AggregateFunction) -> Self {
+        match function {
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::Sum => Accumulator::Sum(None),
+            AggregateFunction::Avg => Accumulator::Avg {
+                ints: 0,
+                floats: 0.0,
+                count: 0,
+            },
+            AggregateFunction::Min => Accumulator::Min(None),
+            AggregateFunction::Max => Accumulator::Max(None),
+        }
+    }
+
+    /// Checks the aggregate can be applied to a column of `datatype`.
+    pub fn check(aggregate: &Aggregate, datatype: &DataType) -> Result<(), TableError> {
+        match aggregate.function {
+            AggregateFunction::Sum | AggregateFunction::Avg if !datatype.is_numeric() => Err(
+                TableError::AggregateTypeMismatch(aggregate.to_string(), datatype.to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds the value of one row; `None` stands for `count()`, which counts rows
+    /// rather than values. Nulls are skipped by every aggregate.
+    pub fn add(&mut self, value: Option<&Data>) -> Result<(), TableError> {
+        if value.is_some_and(|value| value.is_null()) {
+            return Ok(());
+        }
+        match (self, value) {
+            (Accumulator::Count(count), _) => *count += 1,
+            (Accumulator::Sum(sum), Some(value)) => {
+                *sum = Some(match (sum.take(), value) {
+                    (None, value) => value.clone(),
+                    (Some(Data::Int(Some(a))), Data::Int(Some(b))) => {
+                        Data::Int(Some(a.checked_add(*b).ok_or(TableError::IntegerOverflow)?))
+                    }
+                    (Some(Data::Float(Some(a))), Data::Float(Some(b))) => {
+                        let sum = a + b;
+                        match sum.is_finite() {
+                            true => Data::Float(Some(sum)),
+                            false => return Err(TableError::FloatOverflow),
+                        }
+                    }
+                    (Some(sum), _) => sum,
+                })
+            }
+            (
+                Accumulator::Avg {
+                    ints,
+                    floats,
+                    count,
+                },
+                Some(value),
+            ) => {
+                match value {
+                    Data::Int(Some(value)) => *ints += *value as i128,
+                    Data::Float(Some(value)) => {
+                        *floats += value;
+                        if !floats.is_finite() {
+                            return Err(TableError::FloatOverflow);
+                        }
+                    }
+                    _ => return Ok(()),
+                }
+                *count += 1;
+            }
+            (Accumulator::Min(min), Some(value))
+                if min.as_ref().is_none_or(|min| value.sort_cmp(min).is_lt()) =>
+            {
+                *min = Some(value.clone())
+            }
+            (Accumulator::Max(max), Some(value))
+                if max.as_ref().is_none_or(|max| value.sort_cmp(max).is_gt()) =>
+            {
+                *max = Some(value.clone())
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Final value, typed after the column the aggregate ran over. Aggregates
+    /// other than `count` are null when no value was seen.
+    pub fn finish(self, datatype: Option<&DataType>) -> Data {
+        let null = || datatype.map_or(Data::Int(None), DataType::null);
+        match self {
+            Accumulator::Count(count) => Data::Int(Some(count)),
+            Accumulator::Avg { count: 0, .. } => Data::Float(None),
+            Accumulator::Avg {
+                ints,
+                floats,
+                count,
+            } => Data::Float(Some((ints as f64 + floats) / count as f64)),
+            Accumulator::Sum(value) | Accumulator::Min(value) | Accumulator::Max(value) => {
+                value.unwrap_or_else(null)
+            }
+        }
+    }
+}
+
+/// Values of the `group by` columns shared by the rows of one group. Keys are
+/// compared with `Data::sort_cmp`: the nulls of a column form a single group and
+/// floats use their total order, with `-0.0` folded into `0.0` so that equal
+/// numbers always land in the same group.
+#[derive(Debug, Clone)]
+pub struct GroupKey(pub Vec<Data>);
+
+impl GroupKey {
+    pub fn new(values: Vec<Data>) -> Self {
+        let values = values
+            .into_iter()
+            .map(|value| match value {
+                // the pattern also matches -0.0
+                Data::Float(Some(0.0)) => Data::Float(Some(0.0)),
+                value => value,
+            })
+            .collect();
+        GroupKey(values)
+    }
+}
+
+impl Ord for GroupKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| a.sort_cmp(b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for GroupKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for GroupKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for GroupKey {}
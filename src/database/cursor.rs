@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::data::Data;
+
+// tokens have to lex as a single identifier, so bytes are spelled with letters only
+const PREFIX: char = 'z';
+
+/// Position just past the last row of a page. Holds the row's sort key values and
+/// its id so the next page can resume there regardless of rows inserted meanwhile,
+/// along with the order it was made for, as `field asc nulls last` per key, since
+/// the position means nothing under another order.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Cursor {
+    pub row_id: u64,
+    pub order: Vec<String>,
+    pub keys: Vec<Data>,
+}
+
+impl Cursor {
+    pub fn new(row_id: u64, order: Vec<String>, keys: Vec<Data>) -> Self {
+        Self {
+            row_id,
+            order,
+            keys,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let config = bincode::config::standard();
+        let bytes = bincode::serde::encode_to_vec(self, config).expect("Unable to encode cursor");
+
+        let mut token = String::from(PREFIX);
+        for byte in bytes {
+            token.push((b'a' + (byte >> 4)) as char);
+            token.push((b'a' + (byte & 0x0f)) as char);
+        }
+        token
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let letters = token.strip_prefix(PREFIX)?.as_bytes();
+        if letters.len() % 2 != 0 {
+            return None;
+        }
+
+        let mut bytes = Vec::new();
+        for pair in letters.chunks(2) {
+            let high = pair[0].checked_sub(b'a').filter(|n| *n < 16)?;
+            let low = pair[1].checked_sub(b'a').filter(|n| *n < 16)?;
+            bytes.push((high << 4) | low);
+        }
+
+        let config = bincode::config::standard();
+        let (cursor, read) = bincode::serde::decode_from_slice(&bytes, config).ok()?;
+        if read != bytes.len() {
+            return None;
+        }
+        Some(cursor)
+    }
+}
@@ -1,34 +1,128 @@
-use std::fmt::Display;
+use std::{cmp::Ordering, fmt::Display};
 
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
-use crate::bql::token::TokenType;
+use crate::{bql::token::TokenType, database::table::TableError};
 
-#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, PartialOrd)]
+/// A value; `None` inside a variant is a null of that type. `Null` is the
+/// untyped `null` literal, which takes the type of the column it is stored in.
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub enum Data {
     Int(Option<i64>),
     Float(Option<f64>),
     String(Option<String>),
     Boolean(Option<bool>),
+    Null,
+}
+
+/// The type of a column.
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Copy)]
+pub enum DataType {
+    Int,
+    Float,
+    String,
+    Boolean,
+}
+
+impl DataType {
+    /// The null value of this type, e.g. `Int(None)` for `Int`.
+    pub fn null(&self) -> Data {
+        match self {
+            DataType::Int => Data::Int(None),
+            DataType::Float => Data::Float(None),
+            DataType::String => Data::String(None),
+            DataType::Boolean => Data::Boolean(None),
+        }
+    }
+    /// Whether `data` can be stored in or compared with a column of this type.
+    pub fn accepts(&self, data: &Data) -> bool {
+        data.datatype().is_none_or(|datatype| datatype == *self)
+    }
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, DataType::Int | DataType::Float)
+    }
+    /// Whether values of the two types can be compared, which numbers of
+    /// either type can.
+    pub fn is_comparable(&self, other: &DataType) -> bool {
+        self == other || (self.is_numeric() && other.is_numeric())
+    }
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DataType::Int => "Int".blue(),
+            DataType::Float => "Float".cyan(),
+            DataType::String => "String".green(),
+            DataType::Boolean => "Boolean".purple(),
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl Data {
-    pub fn same_type(&self, other: &Self) -> bool {
+    /// The type of the value, `None` for the untyped `null` literal.
+    pub fn datatype(&self) -> Option<DataType> {
+        match self {
+            Data::Int(_) => Some(DataType::Int),
+            Data::Float(_) => Some(DataType::Float),
+            Data::String(_) => Some(DataType::String),
+            Data::Boolean(_) => Some(DataType::Boolean),
+            Data::Null => None,
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(
+            self,
+            Data::Int(None)
+                | Data::Float(None)
+                | Data::String(None)
+                | Data::Boolean(None)
+                | Data::Null
+        )
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Data::Int(Some(i)) => Some(*i as f64),
+            Data::Float(Some(f)) => Some(*f),
+            _ => None,
+        }
+    }
+    /// Orders two values of the same type, with floats compared by `f64::total_cmp`.
+    /// Nulls of any type equal each other and come before every value. Values
+    /// always come from one column, so two different types never meet here.
+    pub fn sort_cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
-            (Data::Int(_), Data::Int(_)) => true,
-            (Data::Float(_), Data::Float(_)) => true,
-            (Data::String(_), Data::String(_)) => true,
-            (Data::Boolean(_), Data::Boolean(_)) => true,
-            _ => false,
+            (a, b) if a.is_null() || b.is_null() => (!a.is_null()).cmp(&!b.is_null()),
+            (Data::Int(Some(a)), Data::Int(Some(b))) => a.cmp(b),
+            (Data::Float(Some(a)), Data::Float(Some(b))) => a.total_cmp(b),
+            (Data::String(Some(a)), Data::String(Some(b))) => a.cmp(b),
+            (Data::Boolean(Some(a)), Data::Boolean(Some(b))) => a.cmp(b),
+            (a, b) => unreachable!("`{}` and `{}` have different types", a, b),
         }
     }
-    fn fmt_data_type(&self) -> String {
+    /// The value written the way a query would spell it.
+    pub fn to_literal(&self) -> String {
         match self {
-            Data::Int(_) => "Int".blue().to_string(),
-            Data::Float(_) => "Float".cyan().to_string(),
-            Data::String(_) => "String".green().to_string(),
-            Data::Boolean(_) => "Boolean".purple().to_string(),
+            Data::Float(Some(f)) => format!("{:?}", f),
+            Data::String(Some(s)) => format!("{:?}", s),
+            data => data.fmt_data_value().unwrap_or_else(|| "null".to_owned()),
+        }
+    }
+    /// Orders two values by what they hold, with an Int and a Float compared as
+    /// numbers. `None` when either is null or their types cannot be compared.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Data::Int(Some(a)), Data::Int(Some(b))) => Some(a.cmp(b)),
+            (Data::Float(Some(a)), Data::Float(Some(b))) => a.partial_cmp(b),
+            (Data::Int(Some(a)), Data::Float(Some(b))) => compare_int_float(*a, *b),
+            (Data::Float(Some(a)), Data::Int(Some(b))) => {
+                compare_int_float(*b, *a).map(Ordering::reverse)
+            }
+            (Data::String(Some(a)), Data::String(Some(b))) => Some(a.cmp(b)),
+            (Data::Boolean(Some(a)), Data::Boolean(Some(b))) => Some(a.cmp(b)),
+            _ => None,
         }
     }
     fn fmt_data_value(&self) -> Option<String> {
@@ -42,13 +136,35 @@ impl Data {
     }
 }
 
+/// Compares exactly, without rounding the Int to the nearest Float the way
+/// `as f64` would for values beyond 2^53.
+fn compare_int_float(int: i64, float: f64) -> Option<Ordering> {
+    // the range of i64 as floats is [-2^63, 2^63)
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+    if float.is_nan() {
+        return None;
+    }
+    if float >= LIMIT {
+        return Some(Ordering::Less);
+    }
+    if float < -LIMIT {
+        return Some(Ordering::Greater);
+    }
+    let whole = float.trunc();
+    match int.cmp(&(whole as i64)) {
+        Ordering::Equal => whole.partial_cmp(&float),
+        ordering => Some(ordering),
+    }
+}
+
 impl Display for Data {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let data_type = self.fmt_data_type();
-        if let Some(value) = self.fmt_data_value() {
-            write!(f, "{}({})", data_type, value.dimmed())
-        } else {
-            write!(f, "{}", data_type)
+        let Some(data_type) = self.datatype() else {
+            return write!(f, "{}", "null".dimmed());
+        };
+        match self.fmt_data_value() {
+            Some(value) => write!(f, "{}({})", data_type, value.dimmed()),
+            None => write!(f, "{}({})", data_type, "null".dimmed()),
         }
     }
 }
@@ -61,18 +177,92 @@ pub enum Comparison {
     LessEquals,
     Greater,
     GreaterEquals,
+    // string patterns
+    Like { ignore_case: bool },
+    Contains { ignore_case: bool },
+    StartsWith { ignore_case: bool },
+    EndsWith { ignore_case: bool },
+    Regex(Pattern),
+}
+
+/// A regular expression compiled once when the query is parsed, so rows are
+/// matched without compiling it again.
+#[derive(Debug, Clone)]
+pub struct Pattern(Box<regex::Regex>);
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(pattern).map(|regex| Pattern(Box::new(regex)))
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
 }
 
 impl Comparison {
-    pub fn apply<T: PartialOrd + PartialEq>(&self, a: &T, b: &T) -> bool {
-        match self {
-            Comparison::Less => a < b,
-            Comparison::LessEquals => a <= b,
-            Comparison::Equals => a == b,
-            Comparison::Greater => a > b,
-            Comparison::GreaterEquals => a >= b,
-            Comparison::NotEquals => a != b,
+    /// Compares two values with three-valued logic: anything compared with a
+    /// null is unknown (`None`). Int and Float compare by value, other types
+    /// only with themselves.
+    pub fn apply(&self, a: &Data, b: &Data) -> Result<Option<bool>, TableError> {
+        if a.is_null() || b.is_null() {
+            return Ok(None);
         }
+        let result = match self {
+            Comparison::Regex(pattern) => match a {
+                Data::String(Some(text)) => pattern.0.is_match(text),
+                _ => false,
+            },
+            Comparison::Like { ignore_case }
+            | Comparison::Contains { ignore_case }
+            | Comparison::StartsWith { ignore_case }
+            | Comparison::EndsWith { ignore_case } => {
+                let (Data::String(Some(text)), Data::String(Some(pattern))) = (a, b) else {
+                    return Ok(Some(false));
+                };
+                let (text, pattern) = match ignore_case {
+                    true => (text.to_lowercase(), pattern.to_lowercase()),
+                    false => (text.clone(), pattern.clone()),
+                };
+                match self {
+                    Comparison::Like { .. } => like(&text, &pattern),
+                    Comparison::Contains { .. } => text.contains(&pattern),
+                    Comparison::StartsWith { .. } => text.starts_with(&pattern),
+                    _ => text.ends_with(&pattern),
+                }
+            }
+            _ => {
+                let ordering = a.compare(b).ok_or_else(|| {
+                    let name = |data: &Data| data.datatype().map(|d| d.to_string());
+                    TableError::IncomparableTypes(
+                        name(a).unwrap_or_default(),
+                        name(b).unwrap_or_default(),
+                    )
+                })?;
+                match self {
+                    Comparison::Less => ordering.is_lt(),
+                    Comparison::LessEquals => ordering.is_le(),
+                    Comparison::Equals => ordering.is_eq(),
+                    Comparison::Greater => ordering.is_gt(),
+                    Comparison::GreaterEquals => ordering.is_ge(),
+                    _ => ordering.is_ne(),
+                }
+            }
+        };
+        Ok(Some(result))
+    }
+    /// Whether the comparison only works on strings.
+    pub fn is_pattern(&self) -> bool {
+        matches!(
+            self,
+            Comparison::Like { .. }
+                | Comparison::Contains { .. }
+                | Comparison::StartsWith { .. }
+                | Comparison::EndsWith { .. }
+                | Comparison::Regex(_)
+        )
     }
     pub fn from_token_type(token_type: &TokenType) -> Option<Self> {
         match token_type {
@@ -85,4 +275,85 @@ impl Comparison {
             _ => None,
         }
     }
+    /// The string patterns, written as words; the `i` versions ignore case.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "like" => Some(Comparison::Like { ignore_case: false }),
+            "ilike" => Some(Comparison::Like { ignore_case: true }),
+            "contains" => Some(Comparison::Contains { ignore_case: false }),
+            "icontains" => Some(Comparison::Contains { ignore_case: true }),
+            "starts" => Some(Comparison::StartsWith { ignore_case: false }),
+            "istarts" => Some(Comparison::StartsWith { ignore_case: true }),
+            "ends" => Some(Comparison::EndsWith { ignore_case: false }),
+            "iends" => Some(Comparison::EndsWith { ignore_case: true }),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let prefix = |ignore_case: &bool| if *ignore_case { "i" } else { "" };
+        match self {
+            Comparison::Equals => write!(f, "=="),
+            Comparison::NotEquals => write!(f, "!="),
+            Comparison::Less => write!(f, "<"),
+            Comparison::LessEquals => write!(f, "<="),
+            Comparison::Greater => write!(f, ">"),
+            Comparison::GreaterEquals => write!(f, ">="),
+            Comparison::Like { ignore_case } => write!(f, "{}like", prefix(ignore_case)),
+            Comparison::Contains { ignore_case } => write!(f, "{}contains", prefix(ignore_case)),
+            Comparison::StartsWith { ignore_case } => {
+                write!(f, "{}starts with", prefix(ignore_case))
+            }
+            Comparison::EndsWith { ignore_case } => write!(f, "{}ends with", prefix(ignore_case)),
+            Comparison::Regex(_) => write!(f, "~"),
+        }
+    }
+}
+
+/// Matches `text` against a `like` pattern, where `%` stands for any run of
+/// characters, `_` for exactly one and `\` makes the next character literal.
+fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    let (mut t, mut p) = (0, 0);
+    // where the last `%` was seen and the text position it is currently covering up to
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                p += 1;
+                backtrack = Some((p, t));
+                continue;
+            }
+            Some('\\') if pattern.get(p + 1) == Some(&text[t]) => {
+                p += 2;
+                t += 1;
+                continue;
+            }
+            Some('_') => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some(c) if *c != '\\' && *c == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+        // mismatch, let the last `%` swallow one more character
+        match backtrack {
+            Some((star_p, star_t)) => {
+                p = star_p;
+                t = star_t + 1;
+                backtrack = Some((star_p, star_t + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '%')
 }
@@ -8,11 +8,15 @@ use crate::database::storage::{self, Record, RecordType};
 use crate::database::table::{Cell, Column, Row, Rows, Table, TableError};
 use crate::utils;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum QueryError {
     TableError(TableError),
     TableDoesNotExist(String),
     TableAlreadyExists(String),
+    SubqueryColumnCount(usize),
+    NotGimme,
+    Storage(String),
 }
 
 impl QueryError {
@@ -25,6 +29,11 @@ impl QueryError {
             QueryError::TableAlreadyExists(table_name) => {
                 format!("Table `{}` already exists", table_name)
             }
+            QueryError::SubqueryColumnCount(count) => {
+                format!("Subquery must return exactly 1 column, found {}", count)
+            }
+            QueryError::NotGimme => "Only `gimme` returns rows".to_string(),
+            QueryError::Storage(message) => message.clone(),
         }
     }
 }
@@ -50,19 +59,22 @@ pub struct Engine<'a> {
 }
 
 impl<'a> Engine<'a> {
-    pub fn new(mut file: &'a mut storage::File) -> Engine<'a> {
-        let tables = Engine::load_tables(&mut file);
-        Engine { tables, file }
+    /// Loads the tables of `file`, failing on files this version can't read.
+    pub fn new(file: &'a mut storage::File) -> Result<Engine<'a>, QueryError> {
+        let tables = Engine::load_tables(file)?;
+        Ok(Engine { tables, file })
     }
-    fn load_tables(file: &mut storage::File) -> Vec<Table> {
-        let records = file.load_records().expect("Unable to read records");
+    fn load_tables(file: &mut storage::File) -> Result<Vec<Table>, QueryError> {
+        let records = file
+            .load_records()
+            .map_err(|err| QueryError::Storage(err.to_string()))?;
         let mut tables = Vec::new();
         for record in records {
             if record.record_type == RecordType::Table {
                 tables.push(record.data)
             }
         }
-        return tables;
+        Ok(tables)
     }
     fn flush(&mut self) {
         let records = Record::from_vec(&self.tables);
@@ -77,14 +89,28 @@ impl<'a> Engine<'a> {
 
     pub fn handle_query(&mut self, query: ast::Query) -> Result<String, QueryError> {
         match query {
-            ast::Query::Gimme(gimme) => match self.gimme(gimme) {
+            ast::Query::Gimme(gimme) => match self.gimme(*gimme) {
                 Ok(rows) => Ok(format!("{}", rows)),
                 Err(e) => Err(e),
             },
             ast::Query::Insert(insert) => match self.insert(insert) {
-                Ok(_) => Ok(utils::format_message(
+                Ok(count) => Ok(utils::format_message(
+                    &"success".bright_green().to_string(),
+                    &format!("Inserted {} row(s)", count),
+                )),
+                Err(e) => Err(e),
+            },
+            ast::Query::Update(update) => match self.update(update) {
+                Ok(count) => Ok(utils::format_message(
+                    &"success".bright_green().to_string(),
+                    &format!("Updated {} row(s)", count),
+                )),
+                Err(e) => Err(e),
+            },
+            ast::Query::Remove(remove) => match self.remove(remove) {
+                Ok(count) => Ok(utils::format_message(
                     &"success".bright_green().to_string(),
-                    &"Inserted row",
+                    &format!("Removed {} row(s)", count),
                 )),
                 Err(e) => Err(e),
             },
@@ -96,6 +122,10 @@ impl<'a> Engine<'a> {
                 Ok(nt) => Ok(format!("{}", nt)),
                 Err(e) => Err(e),
             },
+            ast::Query::AlterTable(alter_table) => match self.alter_table(alter_table) {
+                Ok(table) => Ok(format!("{}", table)),
+                Err(e) => Err(e),
+            },
             ast::Query::DeleteTable(delete_table) => match self.delete_table(&delete_table) {
                 Ok(_) => Ok(utils::format_message(
                     &"success".bright_green().to_string(),
@@ -106,38 +136,180 @@ impl<'a> Engine<'a> {
         }
     }
 
+    /// Runs a `gimme`, returning its typed columns and rows instead of the
+    /// formatted table.
+    pub fn query(&mut self, query: ast::Query) -> Result<Rows, QueryError> {
+        match query {
+            ast::Query::Gimme(gimme) => self.gimme(*gimme),
+            _ => Err(QueryError::NotGimme),
+        }
+    }
+
+    /// Runs the subqueries of `in` conditions, putting the values they return in
+    /// their place.
+    fn resolve_subqueries(&mut self, predicate: &mut ast::Predicate) -> Result<(), QueryError> {
+        match predicate {
+            ast::Predicate::In(condition) => {
+                if let ast::InValues::Query(subquery) = &mut condition.values {
+                    let rows = self.gimme_all((**subquery).clone())?;
+                    if rows.columns.len() != 1 {
+                        return Err(QueryError::SubqueryColumnCount(rows.columns.len()));
+                    }
+                    let values = rows
+                        .rows
+                        .iter()
+                        .filter_map(|row| row.values.get(&rows.columns[0].name))
+                        .map(|cell| cell.data().clone())
+                        .collect();
+                    condition.values = ast::InValues::List(values);
+                }
+                Ok(())
+            }
+            ast::Predicate::Condition(_) | ast::Predicate::IsNull(_) => Ok(()),
+            ast::Predicate::And(left, right) | ast::Predicate::Or(left, right) => {
+                self.resolve_subqueries(left)?;
+                self.resolve_subqueries(right)
+            }
+            ast::Predicate::Not(predicate) => self.resolve_subqueries(predicate),
+        }
+    }
+    fn resolve_where(
+        &mut self,
+        where_statement: &mut Option<ast::Where>,
+    ) -> Result<(), QueryError> {
+        match where_statement {
+            Some(where_statement) => self.resolve_subqueries(&mut where_statement.predicate),
+            None => Ok(()),
+        }
+    }
+
     // GIMME
-    fn gimme(&mut self, gimme: ast::Gimme) -> Result<Rows, QueryError> {
+    fn gimme(&mut self, mut gimme: ast::Gimme) -> Result<Rows, QueryError> {
+        self.resolve_where(&mut gimme.where_statement)?;
+        if let Some(group_statement) = &mut gimme.group_statement
+            && let Some(having) = &mut group_statement.having
+        {
+            self.resolve_subqueries(having)?;
+        }
+
         let table = self
             .get_table_by_name(gimme.table_identifier.value.clone())
-            .ok_or_else(|| QueryError::TableDoesNotExist(gimme.table_identifier.value))?;
+            .ok_or_else(|| QueryError::TableDoesNotExist(gimme.table_identifier.value.clone()))?;
+        if gimme.joins.is_empty() {
+            return table.find(&gimme).map_err(QueryError::TableError);
+        }
+        // joined rows are numbered by their place in this one result, which an
+        // insert into any of the tables shifts, so they can't anchor a cursor
+        if let Some(after) = &gimme.after_statement {
+            return Err(QueryError::TableError(TableError::InvalidCursor(
+                after.cursor.value.clone(),
+            )));
+        }
 
-        let limit_number = match gimme.limit_statement {
-            Some(l) => Some(l.number),
-            None => None,
-        };
+        let mut joined = table.qualified();
+        for join in &gimme.joins {
+            let other = self
+                .get_table_by_name(join.table_identifier.value.clone())
+                .ok_or_else(|| {
+                    QueryError::TableDoesNotExist(join.table_identifier.value.clone())
+                })?;
+            joined = joined.join(join, other).map_err(QueryError::TableError)?;
+        }
+        joined
+            .resolve_fields(&mut gimme)
+            .map_err(QueryError::TableError)?;
+        let mut rows = joined.find(&gimme).map_err(QueryError::TableError)?;
+        rows.cursor = None;
+        Ok(rows)
+    }
 
-        return table
-            .find(&gimme.where_statement, limit_number)
-            .map_err(QueryError::TableError);
+    /// Runs a query nested in another statement, which needs its whole result
+    /// rather than the first page unless it sets its own limit.
+    fn gimme_all(&mut self, mut gimme: ast::Gimme) -> Result<Rows, QueryError> {
+        gimme
+            .limit_statement
+            .get_or_insert(ast::Limit { number: usize::MAX });
+        self.gimme(gimme)
     }
 
     // INSERT
-    fn insert(&mut self, insert: ast::Insert) -> Result<(), QueryError> {
+    fn insert(&mut self, insert: ast::Insert) -> Result<usize, QueryError> {
+        let name = insert.table_identifier.value;
+        if self.get_table_by_name(name.clone()).is_none() {
+            return Err(QueryError::TableDoesNotExist(name));
+        }
+
+        let (rows, columns) = match insert.source {
+            ast::InsertSource::Rows(rows) => {
+                let rows = rows
+                    .into_iter()
+                    .map(|values| {
+                        let mut row_values = HashMap::new();
+                        for item in values {
+                            row_values.insert(item.key.value, Cell::new(item.value));
+                        }
+                        Row::new(row_values)
+                    })
+                    .collect();
+                (rows, None)
+            }
+            ast::InsertSource::Query(gimme) => {
+                let result = self.gimme_all(*gimme)?;
+                (result.rows, Some(result.columns))
+            }
+        };
+
         let table = self
-            .get_table_by_name(insert.table_identifier.value.clone())
-            .ok_or_else(|| QueryError::TableDoesNotExist(insert.table_identifier.value))?;
+            .get_table_by_name(name.clone())
+            .ok_or(QueryError::TableDoesNotExist(name))?;
+        if let Some(columns) = columns {
+            table
+                .check_columns(&columns)
+                .map_err(QueryError::TableError)?;
+        }
+        // the whole batch is written at once, the file is only rewritten on success
+        let count = table.insert_rows(rows).map_err(QueryError::TableError)?;
+        if count > 0 {
+            self.flush();
+        }
+        Ok(count)
+    }
+
+    // UPDATE
+    fn update(&mut self, mut update: ast::Update) -> Result<usize, QueryError> {
+        self.resolve_where(&mut update.where_statement)?;
+        let table = self
+            .get_table_by_name(update.table_identifier.value.clone())
+            .ok_or_else(|| QueryError::TableDoesNotExist(update.table_identifier.value.clone()))?;
 
-        let mut row_values = HashMap::new();
-        for item in insert.values {
-            row_values.insert(item.key.value, Cell::new(item.value));
+        let mut values = HashMap::new();
+        for item in update.values {
+            values.insert(item.key.value, Cell::new(item.value));
         }
 
-        table
-            .insert(Row { values: row_values })
+        let count = table
+            .update(values, &update.where_statement)
             .map_err(QueryError::TableError)?;
-        self.flush();
-        Ok(())
+        if count > 0 {
+            self.flush();
+        }
+        Ok(count)
+    }
+
+    // REMOVE
+    fn remove(&mut self, mut remove: ast::Remove) -> Result<usize, QueryError> {
+        self.resolve_where(&mut remove.where_statement)?;
+        let table = self
+            .get_table_by_name(remove.table_identifier.value.clone())
+            .ok_or_else(|| QueryError::TableDoesNotExist(remove.table_identifier.value.clone()))?;
+
+        let count = table
+            .remove(&remove.where_statement)
+            .map_err(QueryError::TableError)?;
+        if count > 0 {
+            self.flush();
+        }
+        Ok(count)
     }
 
     // TABLES
@@ -152,9 +324,9 @@ impl<'a> Engine<'a> {
             return Err(QueryError::TableAlreadyExists(new_table.identifier.value));
         }
         let columns = new_table
-            .fields
-            .iter()
-            .map(|field| Column::new(field.key.value.clone(), field.value.clone()))
+            .columns
+            .into_iter()
+            .map(|column| Column::new(column.name.value, column.datatype, column.nullable))
             .collect::<Vec<Column>>();
 
         let table = Table::new(new_table.identifier.value, columns);
@@ -163,6 +335,27 @@ impl<'a> Engine<'a> {
 
         Ok(table)
     }
+    /// Changes the columns of a table, rewriting its rows to match, and returns
+    /// the table as it now is.
+    fn alter_table(&mut self, alter_table: ast::AlterTable) -> Result<Table, QueryError> {
+        let table = self
+            .get_table_by_name(alter_table.identifier.value.clone())
+            .ok_or_else(|| QueryError::TableDoesNotExist(alter_table.identifier.value.clone()))?;
+
+        match alter_table.action {
+            ast::AlterAction::AddColumn(column, default) => table.add_column(
+                Column::new(column.name.value, column.datatype, column.nullable),
+                default,
+            ),
+            ast::AlterAction::DropColumn(name) => table.drop_column(&name.value),
+            ast::AlterAction::RenameColumn(from, to) => table.rename_column(&from.value, &to.value),
+        }
+        .map_err(QueryError::TableError)?;
+        let table = table.clone();
+        self.flush();
+
+        Ok(table)
+    }
     fn delete_table(&mut self, delete_table: &ast::DeleteTable) -> Result<(), QueryError> {
         let table_index_to_remove = self
             .tables
@@ -173,6 +366,6 @@ impl<'a> Engine<'a> {
         self.tables.remove(table_index_to_remove);
         self.flush();
 
-        return Ok(());
+        Ok(())
     }
 }
@@ -1,11 +1,38 @@
-use core::fmt;
 use serde::{Deserialize, Serialize};
 use std::{
+    fmt,
     fs::{self, OpenOptions},
     io::{Read, Seek, SeekFrom, Write},
     path::PathBuf,
 };
 
+use crate::database::table::Table;
+
+pub mod legacy;
+
+/// Starts every file written since the format got a version, followed by the
+/// version as a little endian `u32`. Older files start straight with the
+/// records and are read as version 1. Those never start with these bytes: a
+/// count of 66 records encodes as `B`, but is followed by the `RecordType` 0.
+const MAGIC: &[u8; 4] = b"BNDB";
+/// Version 2 added row ids, column nullability and the `null` value.
+const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub struct UnsupportedVersion(u32);
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Database file format version {} is newer than the supported version {}",
+            self.0, FORMAT_VERSION
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub enum RecordType {
     Table,
@@ -21,7 +48,7 @@ impl<T: Serialize + Clone> Record<T> {
     pub fn new(record_type: RecordType, data: T) -> Self {
         Self { record_type, data }
     }
-    pub fn from_vec(objects: &Vec<T>) -> Vec<Self> {
+    pub fn from_vec(objects: &[T]) -> Vec<Self> {
         objects
             .iter()
             .map(|obj| Self::new(RecordType::Table, obj.clone()))
@@ -41,15 +68,16 @@ impl File {
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&file_path)
             .expect("Failed to open database file");
 
         Self { file, file_path }
     }
 
-    pub fn load_records<T: fmt::Debug + Serialize + for<'de> Deserialize<'de>>(
-        &mut self,
-    ) -> Result<Vec<Record<T>>, Box<dyn std::error::Error>> {
+    /// Reads the tables, upgrading files written in an older format. They are
+    /// saved in the current format on the next write.
+    pub fn load_records(&mut self) -> Result<Vec<Record<Table>>, Box<dyn std::error::Error>> {
         let mut buffer = Vec::new();
         self.file.seek(SeekFrom::Start(0))?;
         self.file.read_to_end(&mut buffer)?;
@@ -58,9 +86,25 @@ impl File {
             return Ok(Vec::new());
         }
 
+        let (version, records) = match buffer.strip_prefix(MAGIC) {
+            Some(rest) if rest.len() >= 4 => {
+                let (version, records) = rest.split_at(4);
+                (u32::from_le_bytes(version.try_into()?), records)
+            }
+            _ => (1, buffer.as_slice()),
+        };
         let config = bincode::config::standard();
-        let (records, _) = bincode::serde::decode_from_slice(&buffer, config)?;
-        Ok(records)
+        match version {
+            1 => {
+                let (records, _) = bincode::serde::decode_from_slice(records, config)?;
+                Ok(legacy::upgrade(records)?)
+            }
+            FORMAT_VERSION => {
+                let (records, _) = bincode::serde::decode_from_slice(records, config)?;
+                Ok(records)
+            }
+            version => Err(Box::new(UnsupportedVersion(version))),
+        }
     }
 
     pub fn write_records<T: Serialize>(
@@ -68,7 +112,9 @@ impl File {
         records: Vec<Record<T>>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let config = bincode::config::standard();
-        let encoded = bincode::serde::encode_to_vec(&records, config)?;
+        let mut encoded = MAGIC.to_vec();
+        encoded.extend(FORMAT_VERSION.to_le_bytes());
+        encoded.extend(bincode::serde::encode_to_vec(&records, config)?);
 
         self.file.seek(SeekFrom::Start(0))?;
         self.file.write_all(&encoded)?;
@@ -0,0 +1,102 @@
+//! Layout of database files written before the format was versioned, kept so
+//! they can still be opened. Rows had no ids, columns were typed by an example
+//! value and there was no `null`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::{
+    data::{self, DataType},
+    storage::Record,
+    table::{self, TableError},
+};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Data {
+    Int(Option<i64>),
+    Float(Option<f64>),
+    String(Option<String>),
+    Boolean(Option<bool>),
+}
+
+impl Data {
+    fn datatype(&self) -> DataType {
+        match self {
+            Data::Int(_) => DataType::Int,
+            Data::Float(_) => DataType::Float,
+            Data::String(_) => DataType::String,
+            Data::Boolean(_) => DataType::Boolean,
+        }
+    }
+}
+
+impl From<Data> for data::Data {
+    fn from(data: Data) -> Self {
+        match data {
+            Data::Int(i) => data::Data::Int(i),
+            Data::Float(f) => data::Data::Float(f),
+            Data::String(s) => data::Data::String(s),
+            Data::Boolean(b) => data::Data::Boolean(b),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cell {
+    pub data: Data,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Column {
+    pub name: String,
+    pub datatype: Data,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Row {
+    pub values: HashMap<String, Cell>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<Column>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    /// Rebuilds the table in the current layout. Columns become nullable, as
+    /// new columns are by default, and rows get ids in the order they were
+    /// stored in.
+    fn upgrade(self) -> Result<table::Table, TableError> {
+        let columns = self
+            .columns
+            .into_iter()
+            .map(|column| table::Column::new(column.name, column.datatype.datatype(), true))
+            .collect();
+        let rows = self
+            .rows
+            .into_iter()
+            .map(|row| {
+                table::Row::new(
+                    row.values
+                        .into_iter()
+                        .map(|(name, cell)| (name, table::Cell::new(cell.data.into())))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let mut table = table::Table::new(self.name, columns);
+        table.insert_rows(rows)?;
+        Ok(table)
+    }
+}
+
+pub fn upgrade(records: Vec<Record<Table>>) -> Result<Vec<Record<table::Table>>, TableError> {
+    records
+        .into_iter()
+        .map(|record| Ok(Record::new(record.record_type, record.data.upgrade()?)))
+        .collect()
+}
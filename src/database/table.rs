@@ -1,16 +1,54 @@
-use std::{borrow::Cow, collections::HashMap, fmt::Display};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+};
 
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 
-use crate::{bql::ast::Where, database::data::Data, utils};
+mod alter;
+mod expression;
+mod join;
+
+use crate::{
+    bql::ast::{
+        Aggregate, Expression, Gimme, InValues, NullsOrder, OrderKey, Predicate, Projection,
+        SortDirection, Where,
+    },
+    database::{
+        aggregate::{Accumulator, GroupKey},
+        cursor::Cursor,
+        data::{Comparison, Data, DataType},
+    },
+    utils,
+};
 
 #[derive(Debug, Clone)]
 pub enum TableError {
     RowColumnCountMismatch,
     FieldDoesNotExist(String),
     TypeMismatch(String, String),
+    InvalidCursor(String),
+    AggregateTypeMismatch(String, String),
+    FieldNotAggregated(String),
+    AggregateNotAllowed(String),
+    AmbiguousField(String),
+    TableAlreadyJoined(String),
+    InvalidJoinCondition(String, String),
+    UnresolvedSubquery,
+    PatternTypeMismatch(String, String),
+    NullNotAllowed(String),
+    IntegerOverflow,
+    FloatOverflow,
+    DivisionByZero,
+    ArithmeticTypeMismatch(String, String),
+    IncomparableTypes(String, String),
+    DuplicateColumn(String),
+    DistinctOrder(String),
+    ColumnAlreadyExists(String),
 }
 
 impl Display for TableError {
@@ -25,10 +63,78 @@ impl Display for TableError {
                 "Cell datatype `{}` does not match column datatype `{}`",
                 cell_type, column_type
             ),
+            TableError::InvalidCursor(cursor) => {
+                write!(f, "Cursor `{}` is not valid for this query", cursor)
+            }
+            TableError::AggregateTypeMismatch(aggregate, column_type) => write!(
+                f,
+                "Aggregate `{}` cannot be applied to column datatype `{}`",
+                aggregate, column_type
+            ),
+            TableError::FieldNotAggregated(field) => write!(
+                f,
+                "Field `{}` must be used inside an aggregate when other columns are aggregated",
+                field
+            ),
+            TableError::AggregateNotAllowed(aggregate) => write!(
+                f,
+                "Aggregate `{}` can only be used in a `having` filter",
+                aggregate
+            ),
+            TableError::AmbiguousField(field) => write!(
+                f,
+                "Field `{}` exists in more than one joined table, qualify it as `Table.{}`",
+                field, field
+            ),
+            TableError::TableAlreadyJoined(table) => {
+                write!(f, "Table `{}` is already part of the join", table)
+            }
+            TableError::InvalidJoinCondition(left, right) => write!(
+                f,
+                "Join condition `{} == {}` must compare a column of each table",
+                left, right
+            ),
+            TableError::UnresolvedSubquery => {
+                write!(f, "Subqueries can only be run through the query engine")
+            }
+            TableError::PatternTypeMismatch(comparison, column_type) => write!(
+                f,
+                "`{}` only works on String columns, not `{}`",
+                comparison, column_type
+            ),
+            TableError::NullNotAllowed(column) => {
+                write!(f, "Column `{}` is declared `not null`", column)
+            }
+            TableError::IntegerOverflow => write!(f, "Integer overflow"),
+            TableError::FloatOverflow => write!(f, "Float overflow"),
+            TableError::DivisionByZero => write!(f, "Division by zero"),
+            TableError::ArithmeticTypeMismatch(operator, datatype) => write!(
+                f,
+                "`{}` only works on Int and Float values, not `{}`",
+                operator, datatype
+            ),
+            TableError::IncomparableTypes(left, right) => {
+                write!(f, "`{}` cannot be compared with `{}`", left, right)
+            }
+            TableError::DuplicateColumn(column) => write!(
+                f,
+                "Result column `{}` appears more than once, rename one with `as`",
+                column
+            ),
+            TableError::ColumnAlreadyExists(column) => {
+                write!(f, "Column `{}` already exists", column)
+            }
+            TableError::DistinctOrder(field) => write!(
+                f,
+                "Distinct results can only be ordered by result columns, `{}` is not one",
+                field
+            ),
         }
     }
 }
 
+impl std::error::Error for TableError {}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
 pub struct Cell {
     data: Data,
@@ -38,6 +144,9 @@ impl Cell {
     pub fn new(data: Data) -> Self {
         Self { data }
     }
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
 }
 
 impl Display for Cell {
@@ -49,45 +158,97 @@ impl Display for Cell {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Column {
     name: String,
-    datatype: Data,
+    datatype: DataType,
+    nullable: bool,
 }
 
 impl Column {
-    pub fn new(name: String, datatype: Data) -> Self {
-        Self { name, datatype }
+    pub fn new(name: String, datatype: DataType, nullable: bool) -> Self {
+        Self {
+            name,
+            datatype,
+            nullable,
+        }
+    }
+    /// Checks a cell fits the column, giving a `null` literal the column's type.
+    fn check(&self, cell: &mut Cell) -> Result<(), TableError> {
+        if !self.datatype.accepts(&cell.data) {
+            return Err(TableError::TypeMismatch(
+                cell.data.to_string(),
+                self.datatype.to_string(),
+            ));
+        }
+        if cell.data.is_null() {
+            if !self.nullable {
+                return Err(TableError::NullNotAllowed(self.name.clone()));
+            }
+            cell.data = self.datatype.null();
+        }
+        Ok(())
     }
 }
 
 impl Display for Column {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}({})", self.datatype, self.name.dimmed(),)
+        write!(f, "{}({})", self.datatype, self.name.dimmed())?;
+        if !self.nullable {
+            write!(f, " {}", "not null".dimmed())?;
+        }
+        Ok(())
     }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Row {
+    id: u64,
     pub values: HashMap<String, Cell>,
 }
 
-pub struct Rows<'a>(pub Vec<&'a Row>);
+impl Row {
+    pub fn new(values: HashMap<String, Cell>) -> Self {
+        Self { id: 0, values }
+    }
+    fn select(&self, columns: &[ResultColumn]) -> Row {
+        let values = columns
+            .iter()
+            .filter_map(|column| {
+                self.values
+                    .get(&column.name)
+                    .map(|cell| (column.name.clone(), cell.clone()))
+            })
+            .collect();
+        Row {
+            id: self.id,
+            values,
+        }
+    }
+}
 
-impl<'a> Display for Rows<'a> {
+/// A column of a query result, named after its projection and typed after
+/// what computes it. The type is `None` for a column that can only hold the
+/// untyped `null` literal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultColumn {
+    pub name: String,
+    pub datatype: Option<DataType>,
+}
+
+#[derive(Debug)]
+pub struct Rows {
+    pub columns: Vec<ResultColumn>,
+    pub rows: Vec<Row>,
+    pub cursor: Option<String>,
+}
+
+impl Display for Rows {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut builder = tabled::builder::Builder::default();
+        builder.push_record(self.columns.iter().map(|column| column.name.clone()));
 
-        let mut columns = std::collections::BTreeSet::new();
-        for row in &self.0 {
-            for key in row.values.keys() {
-                columns.insert(key.clone());
-            }
-        }
-        let columns: Vec<String> = columns.into_iter().collect();
-        builder.push_record(columns.iter().cloned());
-
-        for row in &self.0 {
+        for row in &self.rows {
             let mut record = Vec::new();
-            for column in &columns {
-                if let Some(cell) = row.values.get(column) {
+            for column in &self.columns {
+                if let Some(cell) = row.values.get(&column.name) {
                     record.push(cell.to_string());
                 } else {
                     record.push(String::new());
@@ -97,7 +258,18 @@ impl<'a> Display for Rows<'a> {
         }
 
         let mut table = builder.build();
-        write!(f, "{}", utils::format_table(&mut table))
+        write!(f, "{}", utils::format_table(&mut table))?;
+        if let Some(cursor) = &self.cursor {
+            write!(
+                f,
+                "\n{}",
+                utils::format_message(
+                    &"next page".bright_blue().to_string(),
+                    &format!("after {}", cursor)
+                )
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -106,6 +278,7 @@ pub struct Table {
     name: String,
     columns: Vec<Column>,
     rows: Vec<Row>,
+    next_row_id: u64,
 }
 
 impl Display for Table {
@@ -150,6 +323,7 @@ impl Table {
             name,
             columns,
             rows: Vec::new(),
+            next_row_id: 0,
         }
     }
 
@@ -157,60 +331,609 @@ impl Table {
         &self.name
     }
 
-    pub fn insert(&mut self, row: Row) -> Result<(), TableError> {
-        if row.values.len() != self.columns.len() {
-            return Err(TableError::RowColumnCountMismatch);
+    /// Adds the rows only once every one of them has been checked, so a bad row
+    /// leaves the table untouched. Returns how many rows were added.
+    pub fn insert_rows(&mut self, mut rows: Vec<Row>) -> Result<usize, TableError> {
+        for row in rows.iter_mut() {
+            self.check_row(row)?;
         }
 
-        for (key, cell) in row.values.iter() {
-            let column = self
-                .columns
-                .iter()
-                .find(|&c| c.name == *key)
-                .ok_or(TableError::FieldDoesNotExist(key.clone()))?;
+        let count = rows.len();
+        for mut row in rows {
+            row.id = self.next_row_id;
+            self.next_row_id += 1;
+            self.rows.push(row);
+        }
+        Ok(count)
+    }
 
-            if !column.datatype.same_type(&cell.data) {
+    /// Checks that rows with the given columns, like those of a query result,
+    /// fit the table: every column is filled, with values of its type.
+    pub fn check_columns(&self, columns: &[ResultColumn]) -> Result<(), TableError> {
+        for column in columns {
+            let target = self.get_column(&column.name)?;
+            if let Some(datatype) = column.datatype
+                && datatype != target.datatype
+            {
                 return Err(TableError::TypeMismatch(
-                    cell.data.to_string(),
-                    column.datatype.to_string(),
+                    datatype.to_string(),
+                    target.datatype.to_string(),
                 ));
             }
         }
+        if columns.len() != self.columns.len() {
+            return Err(TableError::RowColumnCountMismatch);
+        }
+        Ok(())
+    }
 
-        self.rows.push(row);
+    fn check_row(&self, row: &mut Row) -> Result<(), TableError> {
+        if row.values.len() != self.columns.len() {
+            return Err(TableError::RowColumnCountMismatch);
+        }
+        for (key, cell) in row.values.iter_mut() {
+            self.check_cell(key, cell)?;
+        }
         Ok(())
     }
 
-    pub fn find(
-        &self,
+    pub fn update(
+        &mut self,
+        mut values: HashMap<String, Cell>,
         where_statement: &Option<Where>,
-        limit: Option<usize>,
-    ) -> Result<Rows, TableError> {
-        let limit = limit.unwrap_or(1);
+    ) -> Result<usize, TableError> {
+        for (key, cell) in values.iter_mut() {
+            self.check_cell(key, cell)?;
+        }
+        self.check_where(where_statement)?;
+
+        // find every match before writing so a failing predicate leaves the table untouched
+        let mut matching = Vec::new();
+        for (index, row) in self.rows.iter().enumerate() {
+            if let Some(where_statement) = where_statement
+                && !Self::matches(row, &where_statement.predicate)?
+            {
+                continue;
+            }
+            matching.push(index);
+        }
+
+        for &index in &matching {
+            let row = &mut self.rows[index];
+            for (key, cell) in values.iter() {
+                row.values.insert(key.clone(), cell.clone());
+            }
+        }
+        Ok(matching.len())
+    }
+
+    pub fn remove(&mut self, where_statement: &Option<Where>) -> Result<usize, TableError> {
+        self.check_where(where_statement)?;
+        let Some(where_statement) = where_statement else {
+            let count = self.rows.len();
+            self.rows.clear();
+            return Ok(count);
+        };
+
+        // evaluate every row before dropping any so an error leaves the table untouched
+        let mut keep = Vec::with_capacity(self.rows.len());
+        for row in &self.rows {
+            keep.push(!Self::matches(row, &where_statement.predicate)?);
+        }
+
+        let count = keep.iter().filter(|keep| !**keep).count();
+        let mut keep = keep.into_iter();
+        self.rows.retain(|_| keep.next().unwrap_or(true));
+        Ok(count)
+    }
+
+    fn check_where(&self, where_statement: &Option<Where>) -> Result<(), TableError> {
+        let Some(where_statement) = where_statement else {
+            return Ok(());
+        };
+        // rows are filtered before they are grouped
+        if let Some(aggregate) = where_statement.predicate.aggregates().first() {
+            return Err(TableError::AggregateNotAllowed(aggregate.to_string()));
+        }
+        self.check_predicate(&where_statement.predicate)
+    }
+
+    /// Checks the values of `in` lists have the type of the column they are
+    /// compared with, that arithmetic is only done on numbers and that string
+    /// patterns are only used on strings, before any row is read.
+    fn check_predicate(&self, predicate: &Predicate) -> Result<(), TableError> {
+        match predicate {
+            Predicate::In(condition) => {
+                let column = self.get_column(&condition.field.value)?;
+                let InValues::List(values) = &condition.values else {
+                    return Err(TableError::UnresolvedSubquery);
+                };
+                for value in values {
+                    if value
+                        .datatype()
+                        .is_some_and(|datatype| !column.datatype.is_comparable(&datatype))
+                    {
+                        return Err(TableError::TypeMismatch(
+                            value.to_string(),
+                            column.datatype.to_string(),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Predicate::Condition(condition) => {
+                let datatype = self.expression_type(&condition.expression)?;
+                if !condition.comparison.is_pattern() {
+                    return match (datatype, condition.value.datatype()) {
+                        (Some(left), Some(right)) if !left.is_comparable(&right) => Err(
+                            TableError::IncomparableTypes(left.to_string(), right.to_string()),
+                        ),
+                        _ => Ok(()),
+                    };
+                }
+                if let Some(datatype) = datatype.filter(|d| *d != DataType::String) {
+                    return Err(TableError::PatternTypeMismatch(
+                        condition.comparison.to_string(),
+                        datatype.to_string(),
+                    ));
+                }
+                if !matches!(condition.value, Data::String(Some(_))) {
+                    return Err(TableError::TypeMismatch(
+                        condition.value.to_string(),
+                        DataType::String.to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Predicate::IsNull(condition) => self.get_column(&condition.field.value).map(|_| ()),
+            Predicate::And(left, right) | Predicate::Or(left, right) => {
+                self.check_predicate(left)?;
+                self.check_predicate(right)
+            }
+            Predicate::Not(predicate) => self.check_predicate(predicate),
+        }
+    }
+
+    fn check_cell(&self, key: &str, cell: &mut Cell) -> Result<(), TableError> {
+        self.get_column(key)?.check(cell)
+    }
+
+    pub fn find(&self, gimme: &Gimme) -> Result<Rows, TableError> {
+        let columns = self.project(&gimme.projection)?;
+        self.check_where(&gimme.where_statement)?;
+        Self::check_distinct_order(gimme)?;
+        let aggregated = gimme.projection.as_ref().is_some_and(|projection| {
+            projection
+                .items
+                .iter()
+                .any(|item| !item.expression.aggregates().is_empty())
+        });
+        if aggregated || gimme.group_statement.is_some() {
+            return self.aggregate(gimme, columns);
+        }
+        let order_keys = match &gimme.order_statement {
+            Some(order_statement) => order_statement.keys.as_slice(),
+            None => &[],
+        };
+        for key in order_keys {
+            self.get_column(&key.field.value)?;
+        }
+        let order: Vec<String> = order_keys.iter().map(OrderKey::to_string).collect();
+        let cursor = match &gimme.after_statement {
+            Some(after) => Some(
+                Cursor::decode(&after.cursor.value)
+                    .filter(|cursor| cursor.order == order && cursor.keys.len() == order.len())
+                    .ok_or(TableError::InvalidCursor(after.cursor.value.clone()))?,
+            ),
+            None => None,
+        };
+        let limit = gimme.limit_statement.as_ref().map_or(1, |l| l.number);
+        let offset = gimme.offset_statement.as_ref().map_or(0, |o| o.number);
 
         let mut results = Vec::new();
+        let mut seen = BTreeSet::new();
         for row in &self.rows {
-            if results.len() >= limit {
+            // rows are kept in id order, so without an order the first matches are the answer
+            if order_keys.is_empty() && results.len() >= offset.saturating_add(limit) {
                 break;
             }
-            if let Some(where_statement) = where_statement {
-                let field = &where_statement.field.value;
-                let row_value = row
-                    .values
-                    .get(field)
-                    .ok_or(TableError::FieldDoesNotExist(field.clone()))?;
-
-                if where_statement
-                    .comparison
-                    .apply(&row_value.data, &where_statement.value)
-                {
-                    results.push(row);
+            if let Some(where_statement) = &gimme.where_statement
+                && !Self::matches(row, &where_statement.predicate)?
+            {
+                continue;
+            }
+            // the first row of each value stands for it, also on the pages before the cursor
+            if gimme.distinct {
+                let output = Self::output_row(row, &gimme.projection, &columns)?;
+                if !seen.insert(Self::distinct_key(&output, &columns)) {
+                    continue;
+                }
+            }
+            let keys = Self::sort_keys(row, order_keys);
+            if let Some(cursor) = &cursor {
+                let cursor_keys: Vec<Option<&Data>> = cursor.keys.iter().map(Some).collect();
+                let ordering =
+                    Self::compare_keys((&keys, row.id), (&cursor_keys, cursor.row_id), order_keys);
+                if ordering != Ordering::Greater {
+                    continue;
+                }
+            }
+            results.push((keys, row));
+        }
+
+        if !order_keys.is_empty() {
+            results.sort_by(|(a_keys, a), (b_keys, b)| {
+                Self::compare_keys((a_keys, a.id), (b_keys, b.id), order_keys)
+            });
+        }
+
+        let page: Vec<(Vec<Option<&Data>>, &Row)> =
+            results.into_iter().skip(offset).take(limit).collect();
+        let cursor = match page.last() {
+            Some((keys, row)) if page.len() == limit => Some(
+                Cursor::new(
+                    row.id,
+                    order,
+                    keys.iter().flatten().map(|&data| data.clone()).collect(),
+                )
+                .encode(),
+            ),
+            _ => None,
+        };
+
+        Ok(Rows {
+            rows: page
+                .iter()
+                .map(|(_, row)| Self::output_row(row, &gimme.projection, &columns))
+                .collect::<Result<_, _>>()?,
+            columns,
+            cursor,
+        })
+    }
+
+    fn get_column(&self, name: &str) -> Result<&Column, TableError> {
+        self.columns
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or(TableError::FieldDoesNotExist(name.to_owned()))
+    }
+
+    /// Aggregates the matching rows into one result row per group, or into a
+    /// single row when there is no `group by`.
+    fn aggregate(&self, gimme: &Gimme, columns: Vec<ResultColumn>) -> Result<Rows, TableError> {
+        let (group_keys, having) = match &gimme.group_statement {
+            Some(group_statement) => (
+                group_statement.keys.as_slice(),
+                group_statement.having.as_ref(),
+            ),
+            None => (&[][..], None),
+        };
+        for key in group_keys {
+            self.get_column(&key.value)?;
+        }
+
+        // plain fields are only allowed when they are the same for the whole group
+        let mut aggregates: Vec<&Aggregate> = Vec::new();
+        let mut fields = Vec::new();
+        if let Some(projection) = &gimme.projection {
+            for item in &projection.items {
+                fields.extend(item.expression.fields());
+                aggregates.extend(item.expression.aggregates());
+            }
+        }
+        if let Some(having) = having {
+            self.check_predicate(having)?;
+            fields.extend(having.fields());
+            aggregates.extend(having.aggregates());
+        }
+        if let Some(order_statement) = &gimme.order_statement {
+            fields.extend(order_statement.keys.iter().map(|key| &key.field));
+        }
+        if let Some(field) = fields.iter().find(|field| !group_keys.contains(field)) {
+            return Err(TableError::FieldNotAggregated(field.value.clone()));
+        }
+        if let Some(after) = &gimme.after_statement {
+            return Err(TableError::InvalidCursor(after.cursor.value.clone()));
+        }
+
+        let mut unique: Vec<&Aggregate> = Vec::new();
+        let mut datatypes = Vec::new();
+        for aggregate in aggregates {
+            if unique.contains(&aggregate) {
+                continue;
+            }
+            let datatype = match &aggregate.field {
+                Some(field) => {
+                    let datatype = &self.get_column(&field.value)?.datatype;
+                    Accumulator::check(aggregate, datatype)?;
+                    Some(datatype)
                 }
-            } else {
-                results.push(row);
+                None => None,
+            };
+            unique.push(aggregate);
+            datatypes.push(datatype);
+        }
+        let aggregates = unique;
+        let accumulators = || -> Vec<Accumulator> {
+            aggregates
+                .iter()
+                .map(|aggregate| Accumulator::new(aggregate.function))
+                .collect()
+        };
+
+        let mut groups = BTreeMap::new();
+        // without `group by` all rows share one group, which exists even when none match
+        if group_keys.is_empty() {
+            groups.insert(GroupKey::new(Vec::new()), accumulators());
+        }
+        for row in &self.rows {
+            if let Some(where_statement) = &gimme.where_statement
+                && !Self::matches(row, &where_statement.predicate)?
+            {
+                continue;
+            }
+            let key = group_keys
+                .iter()
+                .map(|key| Self::value(row, &key.value).cloned())
+                .collect::<Result<Vec<Data>, TableError>>()?;
+            let group = groups
+                .entry(GroupKey::new(key))
+                .or_insert_with(accumulators);
+            for (aggregate, accumulator) in aggregates.iter().zip(group.iter_mut()) {
+                let value = match &aggregate.field {
+                    Some(field) => Some(Self::value(row, &field.value)?),
+                    None => None,
+                };
+                accumulator.add(value)?;
             }
         }
 
-        return Ok(Rows(results));
+        let mut results = Vec::new();
+        for (key, group) in groups {
+            let mut values = HashMap::new();
+            for (field, data) in group_keys.iter().zip(key.0) {
+                values.insert(field.value.clone(), Cell::new(data));
+            }
+            for ((aggregate, datatype), accumulator) in aggregates.iter().zip(&datatypes).zip(group)
+            {
+                let data = accumulator.finish(*datatype);
+                values.insert(aggregate.to_string(), Cell::new(data));
+            }
+            let row = Row::new(values);
+            if let Some(having) = having
+                && !Self::matches(&row, having)?
+            {
+                continue;
+            }
+            results.push(row);
+        }
+
+        // groups come out in ascending key order, nulls last, unless ordered otherwise
+        let order_keys: Vec<OrderKey> = match &gimme.order_statement {
+            Some(order_statement) => order_statement.keys.clone(),
+            None => group_keys
+                .iter()
+                .map(|key| OrderKey {
+                    field: key.clone(),
+                    direction: SortDirection::Ascending,
+                    nulls: NullsOrder::Last,
+                })
+                .collect(),
+        };
+        results.sort_by(|a, b| {
+            let a_keys = Self::sort_keys(a, &order_keys);
+            let b_keys = Self::sort_keys(b, &order_keys);
+            Self::compare_keys((&a_keys, a.id), (&b_keys, b.id), &order_keys)
+        });
+
+        let columns = match &gimme.projection {
+            Some(_) => columns,
+            None => group_keys
+                .iter()
+                .map(|key| ResultColumn {
+                    name: key.value.clone(),
+                    datatype: columns
+                        .iter()
+                        .find(|column| column.name == key.value)
+                        .and_then(|column| column.datatype),
+                })
+                .collect(),
+        };
+        let mut rows = Vec::new();
+        let mut seen = BTreeSet::new();
+        for row in &results {
+            let row = Self::output_row(row, &gimme.projection, &columns)?;
+            if gimme.distinct && !seen.insert(Self::distinct_key(&row, &columns)) {
+                continue;
+            }
+            rows.push(row);
+        }
+        let limit = gimme.limit_statement.as_ref().map_or(1, |l| l.number);
+        let offset = gimme.offset_statement.as_ref().map_or(0, |o| o.number);
+        Ok(Rows {
+            rows: rows.into_iter().skip(offset).take(limit).collect(),
+            columns,
+            cursor: None,
+        })
+    }
+
+    /// Distinct results keep one row per value, so an order has to come from
+    /// the values themselves rather than from a row they happen to be taken from.
+    fn check_distinct_order(gimme: &Gimme) -> Result<(), TableError> {
+        let (true, Some(projection), Some(order_statement)) =
+            (gimme.distinct, &gimme.projection, &gimme.order_statement)
+        else {
+            return Ok(());
+        };
+        for key in &order_statement.keys {
+            let field = Expression::Field(key.field.clone());
+            if !projection.items.iter().any(|item| item.expression == field) {
+                return Err(TableError::DistinctOrder(key.field.value.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The values of a result row that tell it apart under `distinct`, equal
+    /// the same way `group by` keys are.
+    fn distinct_key(row: &Row, columns: &[ResultColumn]) -> GroupKey {
+        GroupKey::new(
+            columns
+                .iter()
+                .map(|column| {
+                    row.values
+                        .get(&column.name)
+                        .map_or(Data::Null, |cell| cell.data().clone())
+                })
+                .collect(),
+        )
+    }
+
+    /// The columns of the result, checking every projected expression can be
+    /// computed and that no two columns share a name.
+    fn project(&self, projection: &Option<Projection>) -> Result<Vec<ResultColumn>, TableError> {
+        let Some(projection) = projection else {
+            return Ok(self
+                .columns
+                .iter()
+                .map(|column| ResultColumn {
+                    name: column.name.clone(),
+                    datatype: Some(column.datatype),
+                })
+                .collect());
+        };
+        let mut columns: Vec<ResultColumn> = Vec::new();
+        for item in &projection.items {
+            let name = item.name();
+            if columns.iter().any(|column| column.name == name) {
+                return Err(TableError::DuplicateColumn(name));
+            }
+            columns.push(ResultColumn {
+                datatype: self.expression_type(&item.expression)?,
+                name,
+            });
+        }
+        Ok(columns)
+    }
+
+    /// The row as it appears in a result, with every projected expression
+    /// computed under its column name.
+    fn output_row(
+        row: &Row,
+        projection: &Option<Projection>,
+        columns: &[ResultColumn],
+    ) -> Result<Row, TableError> {
+        let Some(projection) = projection else {
+            return Ok(row.select(columns));
+        };
+        let mut values = HashMap::new();
+        for item in &projection.items {
+            let value = Self::evaluate_expression(row, &item.expression)?.into_owned();
+            values.insert(item.name(), Cell::new(value));
+        }
+        Ok(Row { id: row.id, values })
+    }
+
+    /// Whether the row passes the predicate; rows the predicate is unknown for
+    /// are left out, like those it is false for.
+    fn matches(row: &Row, predicate: &Predicate) -> Result<bool, TableError> {
+        Ok(Self::evaluate(row, predicate)? == Some(true))
+    }
+
+    /// Evaluates a predicate with three-valued logic, where `None` is unknown.
+    fn evaluate(row: &Row, predicate: &Predicate) -> Result<Option<bool>, TableError> {
+        match predicate {
+            Predicate::Condition(condition) => {
+                let row_value = Self::evaluate_expression(row, &condition.expression)?;
+                condition.comparison.apply(&row_value, &condition.value)
+            }
+            Predicate::In(condition) => {
+                let row_value = Self::value(row, &condition.field.value)?;
+                let InValues::List(values) = &condition.values else {
+                    return Err(TableError::UnresolvedSubquery);
+                };
+                // a miss is only certain when no comparison was unknown
+                let mut result = Some(false);
+                for value in values {
+                    match Comparison::Equals.apply(row_value, value)? {
+                        Some(true) => {
+                            result = Some(true);
+                            break;
+                        }
+                        Some(false) => {}
+                        None => result = None,
+                    }
+                }
+                Ok(result.map(|found| found != condition.negated))
+            }
+            Predicate::IsNull(condition) => {
+                let row_value = Self::value(row, &condition.field.value)?;
+                Ok(Some(row_value.is_null() != condition.negated))
+            }
+            Predicate::And(left, right) => match Self::evaluate(row, left)? {
+                Some(false) => Ok(Some(false)),
+                left => Ok(match (left, Self::evaluate(row, right)?) {
+                    (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                }),
+            },
+            Predicate::Or(left, right) => match Self::evaluate(row, left)? {
+                Some(true) => Ok(Some(true)),
+                left => Ok(match (left, Self::evaluate(row, right)?) {
+                    (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                }),
+            },
+            Predicate::Not(predicate) => Ok(Self::evaluate(row, predicate)?.map(|value| !value)),
+        }
+    }
+
+    fn value<'r>(row: &'r Row, field: &str) -> Result<&'r Data, TableError> {
+        row.values
+            .get(field)
+            .map(|cell| &cell.data)
+            .ok_or(TableError::FieldDoesNotExist(field.to_owned()))
+    }
+
+    fn sort_keys<'r>(row: &'r Row, order_keys: &[OrderKey]) -> Vec<Option<&'r Data>> {
+        order_keys
+            .iter()
+            .map(|key| row.values.get(&key.field.value).map(|cell| &cell.data))
+            .collect()
+    }
+
+    /// Orders rows by their sort keys, falling back to insertion order so that
+    /// every row has a unique position a cursor can point at.
+    fn compare_keys(
+        (a_keys, a_id): (&[Option<&Data>], u64),
+        (b_keys, b_id): (&[Option<&Data>], u64),
+        order_keys: &[OrderKey],
+    ) -> Ordering {
+        for ((a, b), key) in a_keys.iter().zip(b_keys).zip(order_keys) {
+            let a = a.filter(|data| !data.is_null());
+            let b = b.filter(|data| !data.is_null());
+
+            let ordering = match (a, b) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => match key.nulls {
+                    NullsOrder::First => Ordering::Less,
+                    NullsOrder::Last => Ordering::Greater,
+                },
+                (Some(_), None) => match key.nulls {
+                    NullsOrder::First => Ordering::Greater,
+                    NullsOrder::Last => Ordering::Less,
+                },
+                (Some(a), Some(b)) => match key.direction {
+                    SortDirection::Ascending => a.sort_cmp(b),
+                    SortDirection::Descending => b.sort_cmp(a),
+                },
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        a_id.cmp(&b_id)
     }
 }
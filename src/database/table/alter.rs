@@ -0,0 +1,53 @@
+use crate::database::{
+    data::Data,
+    table::{Cell, Column, Table, TableError},
+};
+
+impl Table {
+    /// Adds a column at the end, filling it with `default` in every existing
+    /// row so rows keep one value per column. A `not null` column only needs a
+    /// default when there are rows to fill.
+    pub fn add_column(&mut self, column: Column, default: Data) -> Result<(), TableError> {
+        if self.get_column(&column.name).is_ok() {
+            return Err(TableError::ColumnAlreadyExists(column.name));
+        }
+        let mut default = Cell::new(default);
+        match column.check(&mut default) {
+            Err(TableError::NullNotAllowed(_)) if self.rows.is_empty() => {}
+            result => result?,
+        }
+
+        for row in &mut self.rows {
+            row.values.insert(column.name.clone(), default.clone());
+        }
+        self.columns.push(column);
+        Ok(())
+    }
+
+    pub fn drop_column(&mut self, name: &str) -> Result<(), TableError> {
+        self.get_column(name)?;
+        self.columns.retain(|column| column.name != name);
+        for row in &mut self.rows {
+            row.values.remove(name);
+        }
+        Ok(())
+    }
+
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<(), TableError> {
+        self.get_column(from)?;
+        if self.get_column(to).is_ok() {
+            return Err(TableError::ColumnAlreadyExists(to.to_owned()));
+        }
+        for column in &mut self.columns {
+            if column.name == from {
+                column.name = to.to_owned();
+            }
+        }
+        for row in &mut self.rows {
+            if let Some(cell) = row.values.remove(from) {
+                row.values.insert(to.to_owned(), cell);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+
+use crate::{
+    bql::ast::{AggregateFunction, Expression, Operator},
+    database::{
+        aggregate::Accumulator,
+        data::{Data, DataType},
+        table::{Row, Table, TableError},
+    },
+};
+
+impl Table {
+    /// Type of the values an expression produces, `None` when it can only be
+    /// null. Checks that its fields exist and that only numbers are computed on.
+    pub(super) fn expression_type(
+        &self,
+        expression: &Expression,
+    ) -> Result<Option<DataType>, TableError> {
+        match expression {
+            Expression::Field(field) => Ok(Some(self.get_column(&field.value)?.datatype)),
+            Expression::Literal(data) => Ok(data.datatype()),
+            Expression::Aggregate(aggregate) => {
+                let Some(field) = &aggregate.field else {
+                    return Ok(Some(DataType::Int));
+                };
+                let datatype = self.get_column(&field.value)?.datatype;
+                Accumulator::check(aggregate, &datatype)?;
+                Ok(Some(match aggregate.function {
+                    AggregateFunction::Count => DataType::Int,
+                    AggregateFunction::Avg => DataType::Float,
+                    _ => datatype,
+                }))
+            }
+            Expression::Negate(operand) => {
+                let datatype = self.expression_type(operand)?;
+                check_numeric("-", datatype)?;
+                Ok(datatype)
+            }
+            Expression::Binary(left, operator, right) => {
+                let left = self.expression_type(left)?;
+                let right = self.expression_type(right)?;
+                check_numeric(&operator.to_string(), left)?;
+                check_numeric(&operator.to_string(), right)?;
+                Ok(promote(left, right))
+            }
+        }
+    }
+
+    /// Computes the value of an expression for one row.
+    pub(super) fn evaluate_expression<'a>(
+        row: &'a Row,
+        expression: &'a Expression,
+    ) -> Result<Cow<'a, Data>, TableError> {
+        match expression {
+            Expression::Field(field) => Self::value(row, &field.value).map(Cow::Borrowed),
+            Expression::Literal(data) => Ok(Cow::Borrowed(data)),
+            Expression::Aggregate(aggregate) => {
+                // only rows built from a group carry aggregate values
+                let name = aggregate.to_string();
+                match row.values.get(&name) {
+                    Some(cell) => Ok(Cow::Borrowed(&cell.data)),
+                    None => Err(TableError::AggregateNotAllowed(name)),
+                }
+            }
+            Expression::Negate(operand) => {
+                let value = Self::evaluate_expression(row, operand)?;
+                check_numeric("-", value.datatype())?;
+                Ok(Cow::Owned(match value.as_ref() {
+                    Data::Int(Some(i)) => {
+                        Data::Int(Some(i.checked_neg().ok_or(TableError::IntegerOverflow)?))
+                    }
+                    Data::Float(Some(f)) => Data::Float(Some(-f)),
+                    null => null.clone(),
+                }))
+            }
+            Expression::Binary(left, operator, right) => {
+                let left = Self::evaluate_expression(row, left)?;
+                let right = Self::evaluate_expression(row, right)?;
+                apply(&left, *operator, &right).map(Cow::Owned)
+            }
+        }
+    }
+}
+
+fn check_numeric(operator: &str, datatype: Option<DataType>) -> Result<(), TableError> {
+    match datatype {
+        Some(datatype) if !datatype.is_numeric() => Err(TableError::ArithmeticTypeMismatch(
+            operator.to_owned(),
+            datatype.to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Int with Int stays Int, anything involving a Float is computed as Float.
+fn promote(left: Option<DataType>, right: Option<DataType>) -> Option<DataType> {
+    match (left, right) {
+        (Some(DataType::Float), _) | (_, Some(DataType::Float)) => Some(DataType::Float),
+        (Some(DataType::Int), _) | (_, Some(DataType::Int)) => Some(DataType::Int),
+        _ => None,
+    }
+}
+
+/// Applies a binary operator; a null operand makes the result null. Dividing
+/// by zero and results that do not fit the type are errors rather than
+/// wrapping around or turning into infinity.
+fn apply(left: &Data, operator: Operator, right: &Data) -> Result<Data, TableError> {
+    check_numeric(&operator.to_string(), left.datatype())?;
+    check_numeric(&operator.to_string(), right.datatype())?;
+
+    if let (Data::Int(Some(a)), Data::Int(Some(b))) = (left, right) {
+        let result = match operator {
+            Operator::Divide | Operator::Remainder if *b == 0 => {
+                return Err(TableError::DivisionByZero);
+            }
+            Operator::Add => a.checked_add(*b),
+            Operator::Subtract => a.checked_sub(*b),
+            Operator::Multiply => a.checked_mul(*b),
+            Operator::Divide => a.checked_div(*b),
+            Operator::Remainder => a.checked_rem(*b),
+        };
+        return result
+            .map(|result| Data::Int(Some(result)))
+            .ok_or(TableError::IntegerOverflow);
+    }
+
+    let (Some(a), Some(b)) = (left.as_f64(), right.as_f64()) else {
+        let datatype = promote(left.datatype(), right.datatype());
+        return Ok(datatype.map_or(Data::Null, |datatype| datatype.null()));
+    };
+    let result = match operator {
+        Operator::Divide | Operator::Remainder if b == 0.0 => {
+            return Err(TableError::DivisionByZero);
+        }
+        Operator::Add => a + b,
+        Operator::Subtract => a - b,
+        Operator::Multiply => a * b,
+        Operator::Divide => a / b,
+        Operator::Remainder => a % b,
+    };
+    match result.is_finite() {
+        true => Ok(Data::Float(Some(result))),
+        false => Err(TableError::FloatOverflow),
+    }
+}
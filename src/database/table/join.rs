@@ -0,0 +1,236 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    bql::ast::{Expression, Gimme, Identifier, Join, JoinKind, Predicate},
+    database::{
+        data::Data,
+        table::{Cell, Column, Row, Table, TableError},
+    },
+};
+
+/// Join key borrowed from a row. Two floats match when they are `==`, so `-0.0`
+/// matches `0.0`, or when `total_cmp` calls them equal, so a NaN matches the
+/// same NaN. Every other pair matches when `Data::sort_cmp` calls it equal. The
+/// hash folds `-0.0` into `0.0` so matching keys land in the same bucket.
+struct JoinKey<'a>(&'a Data);
+
+impl Hash for JoinKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.0 {
+            Data::Int(i) => i.hash(state),
+            Data::Float(f) => f
+                .map(|f| match f == 0.0 {
+                    true => 0.0_f64.to_bits(),
+                    false => f.to_bits(),
+                })
+                .hash(state),
+            Data::String(s) => s.hash(state),
+            Data::Boolean(b) => b.hash(state),
+            Data::Null => {}
+        }
+    }
+}
+
+impl PartialEq for JoinKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.0, other.0) {
+            (Data::Float(Some(a)), Data::Float(Some(b))) => a == b || a.total_cmp(b).is_eq(),
+            (a, b) => a.sort_cmp(b).is_eq(),
+        }
+    }
+}
+
+impl Eq for JoinKey<'_> {}
+
+impl Table {
+    /// Copy of the table with every column renamed to `Table.column`, the shape
+    /// that joins build on.
+    pub fn qualified(&self) -> Table {
+        let qualify = |name: &str| format!("{}.{}", self.name, name);
+        Table {
+            name: self.name.clone(),
+            columns: self
+                .columns
+                .iter()
+                .map(|column| Column {
+                    name: qualify(&column.name),
+                    ..column.clone()
+                })
+                .collect(),
+            rows: self
+                .rows
+                .iter()
+                .map(|row| Row {
+                    id: row.id,
+                    values: row
+                        .values
+                        .iter()
+                        .map(|(name, cell)| (qualify(name), cell.clone()))
+                        .collect(),
+                })
+                .collect(),
+            next_row_id: self.next_row_id,
+        }
+    }
+
+    /// Joins `other` onto this already qualified table with a hash join on the
+    /// `on` fields. Null keys never match, so with a left join those rows are
+    /// kept with nulls for every column of `other`.
+    pub fn join(&self, join: &Join, other: &Table) -> Result<Table, TableError> {
+        let prefix = format!("{}.", other.name);
+        if self.columns.iter().any(|c| c.name.starts_with(&prefix)) {
+            return Err(TableError::TableAlreadyJoined(other.name.clone()));
+        }
+        let other = other.qualified();
+
+        let (a, b) = &join.on;
+        let a = self
+            .resolve(&a.value)
+            .or_else(|_| other.resolve(&a.value))?;
+        let b = self
+            .resolve(&b.value)
+            .or_else(|_| other.resolve(&b.value))?;
+        let (left, right) = match (self.get_column(&a), other.get_column(&b)) {
+            (Ok(left), Ok(right)) => (left, right),
+            _ => match (self.get_column(&b), other.get_column(&a)) {
+                (Ok(left), Ok(right)) => (left, right),
+                _ => return Err(TableError::InvalidJoinCondition(a, b)),
+            },
+        };
+        if left.datatype != right.datatype {
+            return Err(TableError::TypeMismatch(
+                right.datatype.to_string(),
+                left.datatype.to_string(),
+            ));
+        }
+
+        let mut index: HashMap<JoinKey, Vec<&Row>> = HashMap::new();
+        for row in &other.rows {
+            let key = Self::value(row, &right.name)?;
+            if !key.is_null() {
+                index.entry(JoinKey(key)).or_default().push(row);
+            }
+        }
+
+        let mut columns = self.columns.clone();
+        columns.extend(other.columns.iter().cloned());
+        let mut joined = Table::new(self.name.clone(), columns);
+        for row in &self.rows {
+            let key = Self::value(row, &left.name)?;
+            let matches = match key.is_null() {
+                true => None,
+                false => index.get(&JoinKey(key)),
+            };
+            match matches {
+                Some(matches) => {
+                    for other_row in matches {
+                        let mut values = row.values.clone();
+                        values.extend(other_row.values.clone());
+                        joined.push(values);
+                    }
+                }
+                None if join.kind == JoinKind::Left => {
+                    let mut values = row.values.clone();
+                    for column in &other.columns {
+                        let null = Cell::new(column.datatype.null());
+                        values.insert(column.name.clone(), null);
+                    }
+                    joined.push(values);
+                }
+                None => {}
+            }
+        }
+        Ok(joined)
+    }
+
+    fn push(&mut self, values: HashMap<String, Cell>) {
+        self.rows.push(Row {
+            id: self.next_row_id,
+            values,
+        });
+        self.next_row_id += 1;
+    }
+
+    /// Finds the column a field refers to; a bare `column` is accepted as long
+    /// as only one of the joined tables has it.
+    fn resolve(&self, field: &str) -> Result<String, TableError> {
+        if self.get_column(field).is_ok() {
+            return Ok(field.to_owned());
+        }
+        let mut candidates = self.columns.iter().filter(|column| {
+            column
+                .name
+                .split_once('.')
+                .is_some_and(|(_, name)| name == field)
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(column), None) => Ok(column.name.clone()),
+            (Some(_), Some(_)) => Err(TableError::AmbiguousField(field.to_owned())),
+            (None, _) => Err(TableError::FieldDoesNotExist(field.to_owned())),
+        }
+    }
+
+    fn resolve_identifier(&self, identifier: &mut Identifier) -> Result<(), TableError> {
+        identifier.value = self.resolve(&identifier.value)?;
+        Ok(())
+    }
+
+    fn resolve_expression(&self, expression: &mut Expression) -> Result<(), TableError> {
+        match expression {
+            Expression::Field(field) => self.resolve_identifier(field),
+            Expression::Aggregate(aggregate) => match &mut aggregate.field {
+                Some(field) => self.resolve_identifier(field),
+                None => Ok(()),
+            },
+            Expression::Literal(_) => Ok(()),
+            Expression::Negate(expression) => self.resolve_expression(expression),
+            Expression::Binary(left, _, right) => {
+                self.resolve_expression(left)?;
+                self.resolve_expression(right)
+            }
+        }
+    }
+
+    fn resolve_predicate(&self, predicate: &mut Predicate) -> Result<(), TableError> {
+        match predicate {
+            Predicate::Condition(condition) => self.resolve_expression(&mut condition.expression),
+            Predicate::In(condition) => self.resolve_identifier(&mut condition.field),
+            Predicate::IsNull(condition) => self.resolve_identifier(&mut condition.field),
+            Predicate::And(left, right) | Predicate::Or(left, right) => {
+                self.resolve_predicate(left)?;
+                self.resolve_predicate(right)
+            }
+            Predicate::Not(predicate) => self.resolve_predicate(predicate),
+        }
+    }
+
+    /// Rewrites every field of the query to the qualified column name it refers
+    /// to in this joined table.
+    pub fn resolve_fields(&self, gimme: &mut Gimme) -> Result<(), TableError> {
+        if let Some(projection) = &mut gimme.projection {
+            for item in &mut projection.items {
+                self.resolve_expression(&mut item.expression)?;
+            }
+        }
+        if let Some(where_statement) = &mut gimme.where_statement {
+            self.resolve_predicate(&mut where_statement.predicate)?;
+        }
+        if let Some(group_statement) = &mut gimme.group_statement {
+            for key in &mut group_statement.keys {
+                self.resolve_identifier(key)?;
+            }
+            if let Some(having) = &mut group_statement.having {
+                self.resolve_predicate(having)?;
+            }
+        }
+        if let Some(order_statement) = &mut gimme.order_statement {
+            for key in &mut order_statement.keys {
+                self.resolve_identifier(&mut key.field)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1720 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    bql::{
+        self,
+        ast::{Gimme, Query},
+        lexer::Lexer,
+        parser::Parser,
+    },
+    database::{
+        Database,
+        data::{Data, DataType},
+        storage::{Record, RecordType, legacy},
+        table::{Cell, Column, ResultColumn, Row, Rows, Table, TableError},
+    },
+};
+
+/// Row holding each of `values` under the column name at the same position.
+fn row(columns: &[&str], values: Vec<Data>) -> Row {
+    Row::new(
+        columns
+            .iter()
+            .zip(values)
+            .map(|(name, data)| (name.to_string(), Cell::new(data)))
+            .collect(),
+    )
+}
+
+/// Fixture table with nullable columns, each row listing its values in column order.
+fn table(name: &str, columns: &[(&str, DataType)], rows: Vec<Vec<Data>>) -> Table {
+    let mut table = Table::new(
+        name.to_string(),
+        columns
+            .iter()
+            .map(|(name, datatype)| Column::new(name.to_string(), *datatype, true))
+            .collect(),
+    );
+    let names: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+    table
+        .insert_rows(rows.into_iter().map(|values| row(&names, values)).collect())
+        .unwrap();
+    table
+}
+
+const USER_COLUMNS: [&str; 3] = ["id", "name", "age"];
+
+fn user(id: i64, name: &str, age: Option<i64>) -> Vec<Data> {
+    vec![
+        Data::Int(Some(id)),
+        Data::String(Some(name.to_string())),
+        Data::Int(age),
+    ]
+}
+
+fn insert_user(table: &mut Table, id: i64, name: &str, age: Option<i64>) {
+    table
+        .insert_rows(vec![row(&USER_COLUMNS, user(id, name, age))])
+        .unwrap();
+}
+
+fn users() -> Table {
+    table(
+        "Users",
+        &[
+            ("id", DataType::Int),
+            ("name", DataType::String),
+            ("age", DataType::Int),
+        ],
+        vec![
+            user(1, "Alice", Some(30)),
+            user(2, "Tom", Some(24)),
+            user(3, "Bob", Some(17)),
+        ],
+    )
+}
+
+fn parse(input: &str) -> Query {
+    let mut parser = Parser::new(Lexer::new(input)).unwrap();
+    parser.parse_query().unwrap()
+}
+
+fn parse_gimme(input: &str) -> Gimme {
+    match parse(input) {
+        Query::Gimme(gimme) => *gimme,
+        _ => panic!("Expected Gimme query"),
+    }
+}
+
+fn remove(table: &mut Table, input: &str) -> Result<usize, TableError> {
+    match parse(input) {
+        Query::Remove(remove) => table.remove(&remove.where_statement),
+        _ => panic!("Expected Remove query"),
+    }
+}
+
+fn update(table: &mut Table, input: &str) -> Result<usize, TableError> {
+    match parse(input) {
+        Query::Update(update) => {
+            let values = update
+                .values
+                .into_iter()
+                .map(|item| (item.key.value, Cell::new(item.value)))
+                .collect();
+            table.update(values, &update.where_statement)
+        }
+        _ => panic!("Expected Update query"),
+    }
+}
+
+fn ids(table: &Table, input: &str) -> Vec<Cell> {
+    table
+        .find(&parse_gimme(input))
+        .unwrap()
+        .rows
+        .iter()
+        .map(|row| row.values["id"].clone())
+        .collect()
+}
+
+fn column_names(rows: &Rows) -> Vec<&str> {
+    rows.columns
+        .iter()
+        .map(|column| column.name.as_str())
+        .collect()
+}
+
+fn int_cells(values: &[i64]) -> Vec<Cell> {
+    values
+        .iter()
+        .map(|value| Cell::new(Data::Int(Some(*value))))
+        .collect()
+}
+
+#[test]
+fn find_boolean_predicate() {
+    let table = users();
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where age >= 18 and (name == Alice or name == Tom) limit 10;"
+        ),
+        int_cells(&[1, 2])
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where not age >= 18 or id == 1 limit 10;"
+        ),
+        int_cells(&[1, 3])
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where not (id == 1 or id == 2) limit 10;"
+        ),
+        int_cells(&[3])
+    );
+}
+
+#[test]
+fn find_unknown_field() {
+    let table = users();
+    assert!(
+        table
+            .find(&parse_gimme("gimme Users where email == x;"))
+            .is_err()
+    );
+}
+
+#[test]
+fn find_projection() {
+    let table = users();
+    let gimme = parse_gimme("gimme Users {name, id} where age > 18 limit 5;");
+    let rows = table.find(&gimme).unwrap();
+    assert_eq!(column_names(&rows), vec!["name", "id"]);
+    assert_eq!(rows.rows.len(), 2);
+    assert_eq!(rows.rows[0].values.len(), 2);
+    assert!(!rows.rows[0].values.contains_key("age"));
+}
+
+#[test]
+fn find_projection_unknown_field() {
+    let table = users();
+    let gimme = parse_gimme("gimme Users {id, email};");
+    let err = table.find(&gimme);
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(field)) if field == "email"));
+}
+
+#[test]
+fn find_order_by() {
+    let mut table = users();
+    insert_user(&mut table, 4, "Alice", Some(24));
+    insert_user(&mut table, 5, "Eve", None);
+
+    assert_eq!(
+        ids(&table, "gimme Users order by age desc, name asc limit 10;"),
+        int_cells(&[5, 1, 4, 2, 3])
+    );
+    assert_eq!(
+        ids(&table, "gimme Users order by age limit 10;"),
+        int_cells(&[3, 2, 4, 1, 5])
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users order by age nulls first, id desc limit 3;"
+        ),
+        int_cells(&[5, 3, 4])
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where age > 18 order by name, id desc limit 2;"
+        ),
+        int_cells(&[4, 1])
+    );
+}
+
+#[test]
+fn find_order_by_unknown_field() {
+    let table = users();
+    let err = table.find(&parse_gimme("gimme Users order by email;"));
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(field)) if field == "email"));
+}
+
+#[test]
+fn find_offset() {
+    let table = users();
+    assert_eq!(
+        ids(&table, "gimme Users limit 1 offset 1;"),
+        int_cells(&[2])
+    );
+    assert_eq!(
+        ids(&table, "gimme Users order by age limit 5 offset 1;"),
+        int_cells(&[2, 1])
+    );
+    assert!(ids(&table, "gimme Users limit 5 offset 10;").is_empty());
+}
+
+fn next_cursor(table: &Table, input: &str) -> String {
+    table
+        .find(&parse_gimme(input))
+        .unwrap()
+        .cursor
+        .expect("Expected cursor")
+}
+
+#[test]
+fn find_after_cursor() {
+    let mut table = users();
+    insert_user(&mut table, 4, "Dan", Some(24));
+
+    let query = "gimme Users order by age desc limit 2";
+    assert_eq!(ids(&table, &format!("{};", query)), int_cells(&[1, 2]));
+    let cursor = next_cursor(&table, &format!("{};", query));
+
+    // rows inserted before and after the cursor position do not shift the next page
+    insert_user(&mut table, 5, "Eve", Some(40));
+    insert_user(&mut table, 6, "Fay", Some(10));
+    let next_page = format!("{} after {};", query, cursor);
+    assert_eq!(ids(&table, &next_page), int_cells(&[4, 3]));
+
+    let cursor = next_cursor(&table, &next_page);
+    assert_eq!(
+        ids(&table, &format!("{} after {};", query, cursor)),
+        int_cells(&[6])
+    );
+}
+
+#[test]
+fn find_after_cursor_unordered() {
+    let mut table = users();
+    let cursor = next_cursor(&table, "gimme Users limit 2;");
+    insert_user(&mut table, 4, "Dan", Some(24));
+    assert_eq!(
+        ids(&table, &format!("gimme Users limit 2 after {};", cursor)),
+        int_cells(&[3, 4])
+    );
+}
+
+#[test]
+fn find_after_invalid_cursor() {
+    let table = users();
+    let cursor = next_cursor(&table, "gimme Users order by age limit 1;");
+    for query in [
+        "gimme Users after zabc;".to_string(),
+        "gimme Users after nonsense;".to_string(),
+        format!("gimme Users order by age, name after {};", cursor),
+        // same number of keys, but another order
+        format!("gimme Users order by id after {};", cursor),
+        format!("gimme Users order by age desc after {};", cursor),
+        format!("gimme Users order by age nulls first after {};", cursor),
+        format!(
+            "gimme Users order by id after {};",
+            next_cursor(&table, "gimme Users;")
+        ),
+    ] {
+        let err = table.find(&parse_gimme(&query));
+        assert!(matches!(err, Err(TableError::InvalidCursor(_))));
+    }
+}
+
+fn aggregate(table: &Table, input: &str) -> Vec<Cell> {
+    let rows = table.find(&parse_gimme(input)).unwrap();
+    assert_eq!(rows.rows.len(), 1);
+    rows.columns
+        .iter()
+        .map(|column| rows.rows[0].values[&column.name].clone())
+        .collect()
+}
+
+#[test]
+fn find_aggregates() {
+    let mut table = users();
+    insert_user(&mut table, 4, "Eve", None);
+    assert_eq!(
+        aggregate(
+            &table,
+            "gimme {count(), count(age), sum(age), avg(age), min(name), max(age)} from Users;"
+        ),
+        vec![
+            Cell::new(Data::Int(Some(4))),
+            Cell::new(Data::Int(Some(3))),
+            Cell::new(Data::Int(Some(71))),
+            Cell::new(Data::Float(Some(71.0 / 3.0))),
+            Cell::new(Data::String(Some("Alice".to_string()))),
+            Cell::new(Data::Int(Some(30))),
+        ]
+    );
+    assert_eq!(
+        aggregate(&table, "gimme count() from Users where age > 20;"),
+        int_cells(&[2])
+    );
+}
+
+#[test]
+fn find_aggregates_no_rows() {
+    let table = users();
+    assert_eq!(
+        aggregate(
+            &table,
+            "gimme {count(), sum(age), avg(age), max(name)} from Users where age > 100;"
+        ),
+        vec![
+            Cell::new(Data::Int(Some(0))),
+            Cell::new(Data::Int(None)),
+            Cell::new(Data::Float(None)),
+            Cell::new(Data::String(None)),
+        ]
+    );
+}
+
+#[test]
+fn find_aggregate_errors() {
+    let table = users();
+    let err = table.find(&parse_gimme("gimme sum(name) from Users;"));
+    assert!(matches!(err, Err(TableError::AggregateTypeMismatch(_, _))));
+    let err = table.find(&parse_gimme("gimme {name, count()} from Users;"));
+    assert!(matches!(err, Err(TableError::FieldNotAggregated(field)) if field == "name"));
+    let err = table.find(&parse_gimme("gimme max(email) from Users;"));
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(_))));
+}
+
+#[test]
+fn find_sum_float_overflow() {
+    let table = table(
+        "Big",
+        &[("value", DataType::Float)],
+        vec![
+            vec![Data::Float(Some(f64::MAX))],
+            vec![Data::Float(Some(f64::MAX))],
+        ],
+    );
+    let err = table.find(&parse_gimme("gimme sum(value) from Big;"));
+    assert!(matches!(err, Err(TableError::FloatOverflow)));
+    let err = table.find(&parse_gimme("gimme avg(value) from Big;"));
+    assert!(matches!(err, Err(TableError::FloatOverflow)));
+}
+
+#[test]
+fn find_avg_large_ints() {
+    // neither value fits in an f64, summing them as floats gives ...994
+    let table = table(
+        "Big",
+        &[("value", DataType::Int)],
+        vec![
+            vec![Data::Int(Some(9_007_199_254_740_993))],
+            vec![Data::Int(Some(9_007_199_254_740_997))],
+        ],
+    );
+    let rows = table
+        .find(&parse_gimme("gimme avg(value) from Big;"))
+        .unwrap();
+    assert_eq!(
+        rows.rows[0].values["avg(value)"],
+        Cell::new(Data::Float(Some(9_007_199_254_740_995.0)))
+    );
+}
+
+fn players() -> Table {
+    let rows = [
+        (Some("red"), Some(1.5), 10),
+        (Some("blue"), Some(0.0), 20),
+        (Some("red"), Some(-0.0), 30),
+        (None, None, 40),
+        (Some("blue"), Some(1.5), 50),
+        (None, Some(1.5), 60),
+    ];
+    table(
+        "Players",
+        &[
+            ("team", DataType::String),
+            ("rating", DataType::Float),
+            ("score", DataType::Int),
+        ],
+        rows.into_iter()
+            .map(|(team, rating, score)| {
+                vec![
+                    Data::String(team.map(str::to_string)),
+                    Data::Float(rating),
+                    Data::Int(Some(score)),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn grouped(table: &Table, input: &str) -> Vec<Vec<Cell>> {
+    let rows = table.find(&parse_gimme(input)).unwrap();
+    rows.rows
+        .iter()
+        .map(|row| {
+            rows.columns
+                .iter()
+                .map(|column| row.values[&column.name].clone())
+                .collect()
+        })
+        .collect()
+}
+
+fn cells(rows: Vec<Vec<Data>>) -> Vec<Vec<Cell>> {
+    rows.into_iter()
+        .map(|row| row.into_iter().map(Cell::new).collect())
+        .collect()
+}
+
+#[test]
+fn find_group_by() {
+    let table = players();
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {team, count(), sum(score)} from Players group by team limit 10;"
+        ),
+        cells(vec![
+            vec![
+                Data::String(Some("blue".to_string())),
+                Data::Int(Some(2)),
+                Data::Int(Some(70)),
+            ],
+            vec![
+                Data::String(Some("red".to_string())),
+                Data::Int(Some(2)),
+                Data::Int(Some(40)),
+            ],
+            vec![Data::String(None), Data::Int(Some(2)), Data::Int(Some(100))],
+        ])
+    );
+}
+
+#[test]
+fn find_group_by_float_keys() {
+    let table = players();
+    // 0.0 and -0.0 share a group, nulls form their own
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {rating, count()} from Players group by rating order by rating desc limit 10;"
+        ),
+        cells(vec![
+            vec![Data::Float(None), Data::Int(Some(1))],
+            vec![Data::Float(Some(1.5)), Data::Int(Some(3))],
+            vec![Data::Float(Some(0.0)), Data::Int(Some(2))],
+        ])
+    );
+}
+
+#[test]
+fn find_group_by_having() {
+    let table = players();
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {team} from Players where score > 10 group by team, rating having max(score) >= 50 or team == red limit 10;"
+        ),
+        cells(vec![
+            vec![Data::String(Some("blue".to_string()))],
+            vec![Data::String(Some("red".to_string()))],
+            vec![Data::String(None)],
+        ])
+    );
+    assert!(
+        grouped(
+            &table,
+            "gimme count() from Players group by team having count() > 5;"
+        )
+        .is_empty()
+    );
+}
+
+#[test]
+fn find_group_by_errors() {
+    let table = players();
+    for (query, field) in [
+        ("gimme {team, score} from Players group by team;", "score"),
+        (
+            "gimme {team} from Players group by team having score > 1;",
+            "score",
+        ),
+        (
+            "gimme {team} from Players group by team order by score;",
+            "score",
+        ),
+    ] {
+        let err = table.find(&parse_gimme(query));
+        assert!(matches!(err, Err(TableError::FieldNotAggregated(f)) if f == field));
+    }
+    let err = table.find(&parse_gimme("gimme Players where count() > 1;"));
+    assert!(matches!(err, Err(TableError::AggregateNotAllowed(_))));
+    let err = table.find(&parse_gimme("gimme {team} from Players group by email;"));
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(_))));
+}
+
+fn orders() -> Table {
+    let rows = [
+        (10, Some(1), 5.0),
+        (11, Some(3), 7.5),
+        (12, Some(1), 2.5),
+        (13, None, 1.0),
+        (14, Some(9), 4.0),
+    ];
+    table(
+        "Orders",
+        &[
+            ("id", DataType::Int),
+            ("user_id", DataType::Int),
+            ("total", DataType::Float),
+        ],
+        rows.into_iter()
+            .map(|(id, user_id, total)| {
+                vec![
+                    Data::Int(Some(id)),
+                    Data::Int(user_id),
+                    Data::Float(Some(total)),
+                ]
+            })
+            .collect(),
+    )
+}
+
+/// Runs a query against `Orders` joined with `Users` the way the engine does.
+fn join_find(input: &str) -> Result<Rows, TableError> {
+    let mut gimme = parse_gimme(input);
+    let users = users();
+    let mut joined = orders().qualified();
+    for join in &gimme.joins {
+        joined = joined.join(join, &users)?;
+    }
+    joined.resolve_fields(&mut gimme)?;
+    joined.find(&gimme)
+}
+
+fn column_cells(rows: &Rows, column: &str) -> Vec<Cell> {
+    rows.rows
+        .iter()
+        .map(|row| row.values[column].clone())
+        .collect()
+}
+
+#[test]
+fn find_inner_join() {
+    let rows = join_find(
+        "gimme {Orders.id, name} from Orders join Users on Orders.user_id == Users.id limit 10;",
+    )
+    .unwrap();
+    assert_eq!(column_names(&rows), vec!["Orders.id", "Users.name"]);
+    assert_eq!(column_cells(&rows, "Orders.id"), int_cells(&[10, 11, 12]));
+    assert_eq!(
+        column_cells(&rows, "Users.name"),
+        ["Alice", "Bob", "Alice"]
+            .iter()
+            .map(|name| Cell::new(Data::String(Some(name.to_string()))))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn find_left_join() {
+    let rows = join_find(
+        "gimme Orders left join Users on Users.id == user_id where Users.id == 1 or total < 4.5 order by total limit 10;",
+    )
+    .unwrap();
+    assert_eq!(
+        column_cells(&rows, "Orders.id"),
+        int_cells(&[13, 12, 14, 10])
+    );
+    assert_eq!(
+        column_cells(&rows, "Users.age"),
+        vec![
+            Cell::new(Data::Int(None)),
+            Cell::new(Data::Int(Some(30))),
+            Cell::new(Data::Int(None)),
+            Cell::new(Data::Int(Some(30))),
+        ]
+    );
+}
+
+#[test]
+fn find_join_group_by() {
+    let rows = join_find(
+        "gimme {name, count(), sum(total)} from Orders join Users on user_id == Users.id group by name limit 10;",
+    )
+    .unwrap();
+    assert_eq!(
+        column_cells(&rows, "sum(Orders.total)"),
+        vec![
+            Cell::new(Data::Float(Some(7.5))),
+            Cell::new(Data::Float(Some(7.5))),
+        ]
+    );
+    assert_eq!(column_cells(&rows, "count()"), int_cells(&[2, 1]));
+}
+
+#[test]
+fn find_join_errors() {
+    let err = join_find("gimme {id} from Orders join Users on user_id == Users.id;");
+    assert!(matches!(err, Err(TableError::AmbiguousField(field)) if field == "id"));
+    let err = join_find("gimme Orders join Users on Users.id == Users.age;");
+    assert!(matches!(err, Err(TableError::InvalidJoinCondition(_, _))));
+    let err = join_find("gimme Orders join Users on total == Users.id;");
+    assert!(matches!(err, Err(TableError::TypeMismatch(_, _))));
+    let err = join_find("gimme Orders join Users on user_id == Users.email;");
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(_))));
+    let err = join_find(
+        "gimme Orders join Users on user_id == Users.id join Users on user_id == Users.id;",
+    );
+    assert!(matches!(err, Err(TableError::TableAlreadyJoined(_))));
+}
+
+#[test]
+fn find_in_list() {
+    let table = users();
+    assert_eq!(
+        ids(&table, "gimme Users where id in (3, 1, 7) limit 10;"),
+        int_cells(&[1, 3])
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where name not in (Alice, \"Bob\") limit 10;"
+        ),
+        int_cells(&[2])
+    );
+    assert!(ids(&table, "gimme Users where id in () limit 10;").is_empty());
+}
+
+#[test]
+fn find_in_list_type_mismatch() {
+    let table = Table::new(
+        "Users".to_string(),
+        vec![Column::new("id".to_string(), DataType::Int, true)],
+    );
+    // checked even though there are no rows to compare against
+    let err = table.find(&parse_gimme("gimme Users where id in (1, \"2\");"));
+    assert!(matches!(err, Err(TableError::TypeMismatch(_, _))));
+}
+
+#[test]
+fn find_string_patterns() {
+    let mut table = users();
+    insert_user(&mut table, 4, "alfred_50%", None);
+    for (query, expected) in [
+        ("gimme Users where name like \"Al%\" limit 10;", vec![1]),
+        ("gimme Users where name ilike \"al%\" limit 10;", vec![1, 4]),
+        ("gimme Users where name like \"_o_\" limit 10;", vec![2, 3]),
+        ("gimme Users where name like \"%e\" limit 10;", vec![1]),
+        (
+            "gimme Users where name like \"%5_\\\\%\" limit 10;",
+            vec![4],
+        ),
+        (
+            "gimme Users where name like \"%\" limit 10;",
+            vec![1, 2, 3, 4],
+        ),
+        ("gimme Users where name contains o limit 10;", vec![2, 3]),
+        ("gimme Users where name icontains LI limit 10;", vec![1]),
+        ("gimme Users where name starts with T limit 10;", vec![2]),
+        (
+            "gimme Users where name istarts with a limit 10;",
+            vec![1, 4],
+        ),
+        ("gimme Users where name ends with \"%\" limit 10;", vec![4]),
+        (
+            "gimme Users where not name iends with B limit 10;",
+            vec![1, 2, 4],
+        ),
+    ] {
+        assert_eq!(ids(&table, query), int_cells(&expected), "{}", query);
+    }
+}
+
+#[test]
+fn find_regex_matches() {
+    let pets = table(
+        "Pets",
+        &[("id", DataType::Int), ("name", DataType::String)],
+        vec![
+            vec![Data::Int(Some(1)), Data::String(Some("Bella".to_string()))],
+            vec![Data::Int(Some(2)), Data::String(Some("Abby".to_string()))],
+            vec![Data::Int(Some(3)), Data::String(None)],
+            vec![Data::Int(Some(4)), Data::String(Some("bruno".to_string()))],
+        ],
+    );
+    for (query, expected) in [
+        ("gimme Pets where name ~ \"^[AB]\" limit 10;", vec![1, 2]),
+        ("gimme Pets where name ~ \"b\" limit 10;", vec![2, 4]),
+        ("gimme Pets where name ~ \"(?i)^b\" limit 10;", vec![1, 4]),
+        ("gimme Pets where name ~ \".*\" limit 10;", vec![1, 2, 4]),
+        ("gimme Pets where not name ~ \"^B\" limit 10;", vec![2, 4]),
+    ] {
+        assert_eq!(ids(&pets, query), int_cells(&expected), "{query}");
+    }
+}
+
+#[test]
+fn find_string_pattern_errors() {
+    let table = Table::new(
+        "Users".to_string(),
+        vec![Column::new("age".to_string(), DataType::Int, true)],
+    );
+    let err = table.find(&parse_gimme("gimme Users where age contains \"1\";"));
+    assert!(matches!(err, Err(TableError::PatternTypeMismatch(_, _))));
+    let err = table.find(&parse_gimme("gimme Users where age ~ \"[0-9]\";"));
+    assert!(matches!(err, Err(TableError::PatternTypeMismatch(_, _))));
+    let err = users().find(&parse_gimme("gimme Users where name like 5;"));
+    assert!(matches!(err, Err(TableError::TypeMismatch(_, _))));
+}
+
+fn scores(table: &Table, input: &str) -> Vec<Cell> {
+    let rows = table.find(&parse_gimme(input)).unwrap();
+    column_cells(&rows, "score")
+}
+
+#[test]
+fn find_numeric_coercion() {
+    let players = players();
+    for (query, expected) in [
+        ("gimme Players where rating > 1 limit 10;", vec![10, 50, 60]),
+        ("gimme Players where rating == 0 limit 10;", vec![20, 30]),
+        ("gimme Players where score == 20.0 limit 10;", vec![20]),
+        ("gimme Players where score < 20.5 limit 10;", vec![10, 20]),
+        ("gimme Players where score >= 49.9 limit 10;", vec![50, 60]),
+        (
+            "gimme Players where rating in (0, 2) limit 10;",
+            vec![20, 30],
+        ),
+        (
+            "gimme Players where score + 0.5 > 50 limit 10;",
+            vec![50, 60],
+        ),
+    ] {
+        assert_eq!(scores(&players, query), int_cells(&expected), "{}", query);
+    }
+
+    // exact even where `as f64` would round the Int
+    let big = table(
+        "Big",
+        &[("score", DataType::Int)],
+        vec![vec![Data::Int(Some(9_007_199_254_740_993))]],
+    );
+    for (query, matched) in [
+        ("gimme Big where score > 9007199254740992.0;", true),
+        ("gimme Big where score == 9007199254740992.0;", false),
+        ("gimme Big where score < 1e19;", true),
+        ("gimme Big where score > -1e19;", true),
+    ] {
+        assert_eq!(scores(&big, query).len() == 1, matched, "{}", query);
+    }
+}
+
+#[test]
+fn find_incomparable_types() {
+    let table = players();
+    for query in [
+        "gimme Players where team > 4;",
+        "gimme Players where score == true;",
+        "gimme Players where rating * 2 != red;",
+        // aggregates take the type of the column they summarize
+        "gimme {team} from Players group by team having min(team) > 3;",
+    ] {
+        let err = table.find(&parse_gimme(query));
+        assert!(
+            matches!(err, Err(TableError::IncomparableTypes(..))),
+            "{}",
+            query
+        );
+    }
+    let err = table.find(&parse_gimme("gimme Players where score in (1, 2.5, x);"));
+    assert!(matches!(err, Err(TableError::TypeMismatch(..))));
+}
+
+#[test]
+fn find_three_valued_nulls() {
+    let mut table = users();
+    insert_user(&mut table, 4, "Eve", None);
+    for (query, expected) in [
+        ("gimme Users where age is null limit 10;", vec![4]),
+        ("gimme Users where age is not null limit 10;", vec![1, 2, 3]),
+        ("gimme Users where age == null limit 10;", vec![]),
+        ("gimme Users where age != null limit 10;", vec![]),
+        ("gimme Users where not age > 20 limit 10;", vec![3]),
+        (
+            "gimme Users where age > 20 or name == Eve limit 10;",
+            vec![1, 2, 4],
+        ),
+        (
+            "gimme Users where not (age > 20 and name == Eve) limit 10;",
+            vec![1, 2, 3],
+        ),
+        ("gimme Users where age in (30, null) limit 10;", vec![1]),
+        ("gimme Users where age not in (30, null) limit 10;", vec![]),
+        ("gimme Users where age not in (30) limit 10;", vec![2, 3]),
+    ] {
+        assert_eq!(ids(&table, query), int_cells(&expected), "{}", query);
+    }
+}
+
+#[test]
+fn find_arithmetic_where() {
+    let mut table = users();
+    insert_user(&mut table, 4, "Eve", None);
+    for (query, expected) in [
+        ("gimme Users where age + 1 > 25 limit 10;", vec![1]),
+        ("gimme Users where age * 2 - id == 59 limit 10;", vec![1]),
+        ("gimme Users where -age < -20 limit 10;", vec![1, 2]),
+        ("gimme Users where age % 2 == 1 limit 10;", vec![3]),
+        ("gimme Users where (age + id) / 2 == 13 limit 10;", vec![2]),
+        ("gimme Users where age / 2.0 == 8.5 limit 10;", vec![3]),
+        // a null operand makes the comparison unknown
+        ("gimme Users where age - age == 0 limit 10;", vec![1, 2, 3]),
+    ] {
+        assert_eq!(ids(&table, query), int_cells(&expected), "{}", query);
+    }
+}
+
+#[test]
+fn find_computed_columns() {
+    let table = users();
+    let rows = table
+        .find(&parse_gimme(
+            "gimme {name, age + 1 as next_age, age / 2, age * 1.5} from Users;",
+        ))
+        .unwrap();
+    assert_eq!(
+        column_names(&rows),
+        vec!["name", "next_age", "age / 2", "age * 1.5"]
+    );
+    let row = &rows.rows[0].values;
+    assert_eq!(row["next_age"], Cell::new(Data::Int(Some(31))));
+    assert_eq!(row["age / 2"], Cell::new(Data::Int(Some(15))));
+    assert_eq!(row["age * 1.5"], Cell::new(Data::Float(Some(45.0))));
+
+    let rows = join_find(
+        "gimme {Orders.id, total * 2 as double} from Orders join Users on user_id == Users.id;",
+    )
+    .unwrap();
+    assert_eq!(
+        column_cells(&rows, "double"),
+        vec![Cell::new(Data::Float(Some(10.0)))]
+    );
+}
+
+#[test]
+fn find_arithmetic_errors() {
+    let table = users();
+    for query in [
+        "gimme Users where age / 0 > 1;",
+        "gimme Users where age % (id - id) > 1;",
+        "gimme {age / 0.0} from Users;",
+    ] {
+        let err = table.find(&parse_gimme(query));
+        assert!(matches!(err, Err(TableError::DivisionByZero)), "{}", query);
+    }
+    for query in [
+        "gimme Users where age * 9223372036854775807 > 1;",
+        "gimme Users where -9223372036854775808 - age < 1;",
+        "gimme {-(-9223372036854775808 + age - age)} from Users;",
+    ] {
+        let err = table.find(&parse_gimme(query));
+        assert!(matches!(err, Err(TableError::IntegerOverflow)), "{}", query);
+    }
+    let err = table.find(&parse_gimme("gimme {age * 1e308 * 10.0} from Users;"));
+    assert!(matches!(err, Err(TableError::FloatOverflow)));
+    let err = table.find(&parse_gimme("gimme Users where name + 1 == 2;"));
+    assert!(matches!(err, Err(TableError::ArithmeticTypeMismatch(..))));
+    let err = table.find(&parse_gimme("gimme {-name} from Users;"));
+    assert!(matches!(err, Err(TableError::ArithmeticTypeMismatch(..))));
+    let err = table.find(&parse_gimme("gimme Users where age * 2 like '1%';"));
+    assert!(matches!(err, Err(TableError::PatternTypeMismatch(..))));
+    let err = table.find(&parse_gimme("gimme {age + height} from Users;"));
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(_))));
+}
+
+#[test]
+fn find_group_by_computed_columns() {
+    let table = players();
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {rating, rating * 2 as double, count()} from Players group by rating limit 10;",
+        ),
+        cells(vec![
+            vec![
+                Data::Float(Some(0.0)),
+                Data::Float(Some(0.0)),
+                Data::Int(Some(2))
+            ],
+            vec![
+                Data::Float(Some(1.5)),
+                Data::Float(Some(3.0)),
+                Data::Int(Some(3))
+            ],
+            vec![Data::Float(None), Data::Float(None), Data::Int(Some(1))],
+        ])
+    );
+    let err = table.find(&parse_gimme(
+        "gimme {team, score + 1} from Players group by team;",
+    ));
+    assert!(matches!(err, Err(TableError::FieldNotAggregated(f)) if f == "score"));
+}
+
+fn result_column(name: &str, datatype: Option<DataType>) -> ResultColumn {
+    ResultColumn {
+        name: name.to_string(),
+        datatype,
+    }
+}
+
+#[test]
+fn find_result_schema() {
+    let table = users();
+    let rows = table
+        .find(&parse_gimme(
+            "gimme {name as customer, age * 12 as months, age / 2.0, null as nothing} from Users;",
+        ))
+        .unwrap();
+    assert_eq!(
+        rows.columns,
+        vec![
+            result_column("customer", Some(DataType::String)),
+            result_column("months", Some(DataType::Int)),
+            result_column("age / 2.0", Some(DataType::Float)),
+            result_column("nothing", None),
+        ]
+    );
+    let row = &rows.rows[0].values;
+    assert_eq!(
+        row["customer"],
+        Cell::new(Data::String(Some("Alice".to_string())))
+    );
+    assert_eq!(row["months"], Cell::new(Data::Int(Some(360))));
+    assert_eq!(row["nothing"], Cell::new(Data::Null));
+
+    let rows = table.find(&parse_gimme("gimme Users;")).unwrap();
+    assert_eq!(
+        rows.columns,
+        vec![
+            result_column("id", Some(DataType::Int)),
+            result_column("name", Some(DataType::String)),
+            result_column("age", Some(DataType::Int)),
+        ]
+    );
+
+    let rows = players()
+        .find(&parse_gimme(
+            "gimme {team as side, count() as total, avg(score), max(rating)} from Players group by team;",
+        ))
+        .unwrap();
+    assert_eq!(
+        rows.columns,
+        vec![
+            result_column("side", Some(DataType::String)),
+            result_column("total", Some(DataType::Int)),
+            result_column("avg(score)", Some(DataType::Float)),
+            result_column("max(rating)", Some(DataType::Float)),
+        ]
+    );
+}
+
+#[test]
+fn find_aggregate_expressions() {
+    let table = players();
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {team, sum(score) / count() as mean, max(score) - min(score)} from Players group by team limit 10;",
+        ),
+        cells(vec![
+            vec![
+                Data::String(Some("blue".to_string())),
+                Data::Int(Some(35)),
+                Data::Int(Some(30))
+            ],
+            vec![
+                Data::String(Some("red".to_string())),
+                Data::Int(Some(20)),
+                Data::Int(Some(20))
+            ],
+            vec![Data::String(None), Data::Int(Some(50)), Data::Int(Some(20))],
+        ])
+    );
+    assert_eq!(
+        grouped(
+            &table,
+            "gimme {team} from Players group by team having sum(score) / count() > 30 limit 10;",
+        ),
+        cells(vec![
+            vec![Data::String(Some("blue".to_string()))],
+            vec![Data::String(None)],
+        ])
+    );
+}
+
+#[test]
+fn find_alias_errors() {
+    let table = users();
+    for query in [
+        "gimme {name, age as name} from Users;",
+        "gimme {age + 1, age + 1} from Users;",
+    ] {
+        let err = table.find(&parse_gimme(query));
+        assert!(
+            matches!(err, Err(TableError::DuplicateColumn(ref c)) if c == "name" || c == "age + 1"),
+            "{}",
+            query
+        );
+    }
+    let err = table.find(&parse_gimme("gimme Users where count() > 1;"));
+    assert!(matches!(err, Err(TableError::AggregateNotAllowed(f)) if f == "count()"));
+    let err = table.find(&parse_gimme("gimme {name, count() + 1} from Users;"));
+    assert!(matches!(err, Err(TableError::FieldNotAggregated(f)) if f == "name"));
+}
+
+#[test]
+fn find_distinct() {
+    let table = players();
+    for (query, expected) in [
+        // -0.0 and 0.0 are the same value, nulls are all the same
+        (
+            "gimme distinct {rating} from Players limit 10;",
+            vec![
+                vec![Data::Float(Some(1.5))],
+                vec![Data::Float(Some(0.0))],
+                vec![Data::Float(None)],
+            ],
+        ),
+        (
+            "gimme distinct {team} from Players limit 10;",
+            vec![
+                vec![Data::String(Some("red".to_string()))],
+                vec![Data::String(Some("blue".to_string()))],
+                vec![Data::String(None)],
+            ],
+        ),
+        (
+            "gimme distinct {score % 20 as rest} from Players limit 10;",
+            vec![vec![Data::Int(Some(10))], vec![Data::Int(Some(0))]],
+        ),
+        (
+            "gimme distinct {team, rating} from Players where score > 10 limit 10;",
+            vec![
+                vec![
+                    Data::String(Some("blue".to_string())),
+                    Data::Float(Some(0.0)),
+                ],
+                vec![
+                    Data::String(Some("red".to_string())),
+                    Data::Float(Some(-0.0)),
+                ],
+                vec![Data::String(None), Data::Float(None)],
+                vec![
+                    Data::String(Some("blue".to_string())),
+                    Data::Float(Some(1.5)),
+                ],
+                vec![Data::String(None), Data::Float(Some(1.5))],
+            ],
+        ),
+        // limit and offset count distinct rows
+        (
+            "gimme distinct {team} from Players limit 1 offset 1;",
+            vec![vec![Data::String(Some("blue".to_string()))]],
+        ),
+        (
+            "gimme distinct {rating} from Players order by rating desc nulls last limit 2;",
+            vec![vec![Data::Float(Some(1.5))], vec![Data::Float(Some(0.0))]],
+        ),
+        (
+            "gimme distinct {count()} from Players group by team limit 10;",
+            vec![vec![Data::Int(Some(2))]],
+        ),
+        (
+            "gimme distinct {team, count()} from Players group by team limit 1 offset 1;",
+            vec![vec![
+                Data::String(Some("red".to_string())),
+                Data::Int(Some(2)),
+            ]],
+        ),
+    ] {
+        assert_eq!(grouped(&table, query), cells(expected), "{}", query);
+    }
+}
+
+#[test]
+fn find_distinct_after_cursor() {
+    let table = players();
+    let query = "gimme distinct {team} from Players order by team limit 1";
+    let mut teams = Vec::new();
+    let mut page = format!("{};", query);
+    while let Ok(rows) = table.find(&parse_gimme(&page)) {
+        teams.extend(rows.rows.iter().map(|row| row.values["team"].clone()));
+        let Some(cursor) = rows.cursor else { break };
+        page = format!("{} after {};", query, cursor);
+    }
+    // every value shows up once across pages
+    assert_eq!(
+        teams,
+        vec![
+            Cell::new(Data::String(Some("blue".to_string()))),
+            Cell::new(Data::String(Some("red".to_string()))),
+            Cell::new(Data::String(None)),
+        ]
+    );
+
+    let err = table.find(&parse_gimme(
+        "gimme distinct {team} from Players order by score;",
+    ));
+    assert!(matches!(err, Err(TableError::DistinctOrder(f)) if f == "score"));
+}
+
+#[test]
+fn insert_null() {
+    let mut table = Table::new(
+        "Users".to_string(),
+        vec![
+            Column::new("id".to_string(), DataType::Int, false),
+            Column::new("email".to_string(), DataType::String, true),
+        ],
+    );
+    let user = |id: Data, email: Data| row(&["id", "email"], vec![id, email]);
+    table
+        .insert_rows(vec![user(Data::Int(Some(1)), Data::Null)])
+        .unwrap();
+    let err = table.insert_rows(vec![user(Data::Null, Data::Null)]);
+    assert!(matches!(err, Err(TableError::NullNotAllowed(column)) if column == "id"));
+    let err = table.insert_rows(vec![user(Data::Int(None), Data::Null)]);
+    assert!(matches!(err, Err(TableError::NullNotAllowed(_))));
+
+    // the literal takes the type of its column
+    let rows = table
+        .find(&parse_gimme("gimme Users where email is null;"))
+        .unwrap();
+    assert_eq!(rows.rows[0].values["email"], Cell::new(Data::String(None)));
+
+    let err = update(&mut table, "update Users set {id: null};");
+    assert!(matches!(err, Err(TableError::NullNotAllowed(_))));
+}
+
+#[test]
+fn insert_rows_all_or_nothing() {
+    let mut table = users();
+    let user = |id: i64, age: Data| row(&USER_COLUMNS, vec![Data::Int(Some(id)), Data::Null, age]);
+    let err = table.insert_rows(vec![
+        user(4, Data::Int(Some(1))),
+        user(5, Data::String(Some("old".to_string()))),
+    ]);
+    assert!(matches!(err, Err(TableError::TypeMismatch(..))));
+    assert_eq!(ids(&table, "gimme Users limit 10;"), int_cells(&[1, 2, 3]));
+
+    let count = table
+        .insert_rows(vec![user(4, Data::Int(Some(1))), user(5, Data::Null)])
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(
+        ids(&table, "gimme Users limit 10;"),
+        int_cells(&[1, 2, 3, 4, 5])
+    );
+}
+
+#[test]
+fn insert_batch() {
+    let script = "new table Users {id: Int, name: String};
+        insert [{id: 1, name: Ann}, {id: 2, name: Bo}, {id: 3, name: null}] into Users;";
+    let output = run("insert_batch", script).unwrap();
+    assert!(output.contains("Inserted 3 row(s)"), "{}", output);
+
+    let output = run(
+        "insert_batch_atomic",
+        &format!(
+            "{} insert [{{id: 4, name: Cy}}, {{id: five, name: Di}}] into Users;
+            gimme count() from Users;",
+            script
+        ),
+    )
+    .unwrap();
+    assert!(output.contains("Int(3)"), "{}", output);
+}
+
+#[test]
+fn alter_add_column() {
+    let mut table = users();
+    let email = || Column::new("email".to_string(), DataType::String, true);
+    table.add_column(email(), Data::Null).unwrap();
+    assert_eq!(
+        ids(&table, "gimme Users where email is null limit 10;"),
+        int_cells(&[1, 2, 3])
+    );
+    let err = table.add_column(email(), Data::Null);
+    assert!(matches!(err, Err(TableError::ColumnAlreadyExists(c)) if c == "email"));
+
+    // rows need a value for the new column from now on
+    let err = table.insert_rows(vec![row(
+        &USER_COLUMNS,
+        vec![Data::Int(Some(4)), Data::Null, Data::Null],
+    )]);
+    assert!(matches!(err, Err(TableError::RowColumnCountMismatch)));
+    table
+        .insert_rows(vec![row(
+            &["id", "name", "age", "email"],
+            vec![
+                Data::Int(Some(4)),
+                Data::Null,
+                Data::Null,
+                Data::String(Some("d@example.com".to_string())),
+            ],
+        )])
+        .unwrap();
+
+    let score = || Column::new("score".to_string(), DataType::Int, false);
+    let err = table.add_column(score(), Data::Null);
+    assert!(matches!(err, Err(TableError::NullNotAllowed(c)) if c == "score"));
+    let err = table.add_column(score(), Data::Float(Some(1.0)));
+    assert!(matches!(err, Err(TableError::TypeMismatch(..))));
+    table.add_column(score(), Data::Int(Some(5))).unwrap();
+    assert_eq!(
+        ids(&table, "gimme Users where score == 5 limit 10;"),
+        int_cells(&[1, 2, 3, 4])
+    );
+
+    // with no rows to fill there is nothing to default
+    let mut table = Table::new("Empty".to_string(), Vec::new());
+    table.add_column(score(), Data::Null).unwrap();
+    let err = table.insert_rows(vec![row(&["score"], vec![Data::Null])]);
+    assert!(matches!(err, Err(TableError::NullNotAllowed(c)) if c == "score"));
+    let err = table.add_column(
+        Column::new("email".to_string(), DataType::String, false),
+        Data::Int(Some(1)),
+    );
+    assert!(matches!(err, Err(TableError::TypeMismatch(..))));
+}
+
+#[test]
+fn alter_drop_and_rename_column() {
+    let mut table = users();
+    table.rename_column("age", "years").unwrap();
+    assert_eq!(
+        ids(&table, "gimme Users where years > 20 limit 10;"),
+        int_cells(&[1, 2])
+    );
+    let err = table.find(&parse_gimme("gimme Users where age > 20;"));
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(f)) if f == "age"));
+    let err = table.rename_column("years", "name");
+    assert!(matches!(err, Err(TableError::ColumnAlreadyExists(c)) if c == "name"));
+    let err = table.rename_column("age", "old");
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(f)) if f == "age"));
+
+    table.drop_column("years").unwrap();
+    let rows = table.find(&parse_gimme("gimme Users;")).unwrap();
+    assert_eq!(column_names(&rows), vec!["id", "name"]);
+    assert_eq!(rows.rows[0].values.len(), 2);
+    table
+        .insert_rows(vec![row(
+            &["id", "name"],
+            vec![Data::Int(Some(4)), Data::Null],
+        )])
+        .unwrap();
+    let err = table.drop_column("years");
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(_))));
+}
+
+#[test]
+fn alter_table_statements() {
+    let output = run(
+        "alter_table_statements",
+        "new table Users {id: Int, name: String};
+        insert [{id: 1, name: Ann}, {id: 2, name: Bo}] into Users;
+        alter table Users add column email: String default none;
+        alter table Users rename column name to nickname;
+        alter table Users drop column id;
+        insert {nickname: Cy, email: 'cy@example.com'} into Users;
+        gimme count() from Users where email is null;",
+    )
+    .unwrap();
+    assert!(output.contains("Int(2)"), "{}", output);
+}
+
+const ORDERS_BY_YEAR: &str = "
+    new table Orders {id: Int, year: Int, total: Float};
+    new table ArchivedOrders {id: Int, year: Int, total: Float not null};
+    insert [
+        {id: 1, year: 2022, total: 5.0},
+        {id: 2, year: 2023, total: null},
+        {id: 3, year: 2024, total: 7.5},
+        {id: 4, year: 2021, total: 1.0},
+    ] into Orders;
+";
+
+#[test]
+fn insert_from_query() {
+    let output = run(
+        "insert_from_query",
+        &format!(
+            "{} insert from (gimme Orders where year < 2024 and total is not null) into ArchivedOrders;",
+            ORDERS_BY_YEAR
+        ),
+    )
+    .unwrap();
+    assert!(output.contains("Inserted 2 row(s)"), "{}", output);
+
+    // columns are matched by name, so a computed one can take the place of another
+    let output = run(
+        "insert_from_query_renamed",
+        &format!(
+            "{} insert from (gimme {{total, id + 100 as id, year}} from Orders where total > 2) into ArchivedOrders;
+            gimme {{count(), min(id)}} from ArchivedOrders;",
+            ORDERS_BY_YEAR
+        ),
+    )
+    .unwrap();
+    assert!(
+        output.contains("Int(2)") && output.contains("Int(101)"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn insert_from_query_errors() {
+    for (query, error) in [
+        (
+            "insert from (gimme {id, year} from Orders) into ArchivedOrders;",
+            "Row length does not match",
+        ),
+        (
+            "insert from (gimme {id, year, total, total as price} from Orders) into ArchivedOrders;",
+            "Field `price` does not exist",
+        ),
+        (
+            "insert from (gimme {id, year, year as total} from Orders) into ArchivedOrders;",
+            "Cell datatype `Int` does not match column datatype `Float`",
+        ),
+        (
+            "insert from (gimme Orders) into Missing;",
+            "Table `Missing` does not exist",
+        ),
+    ] {
+        let err = run(
+            "insert_from_query_errors",
+            &format!("{} {}", ORDERS_BY_YEAR, query),
+        )
+        .unwrap_err();
+        assert!(err.contains(error), "{}: {}", query, err);
+    }
+
+    // the null total of order 2 stops the whole copy
+    let output = run(
+        "insert_from_query_atomic",
+        &format!(
+            "{} insert from (gimme Orders where year < 2024) into ArchivedOrders;
+            gimme count() from ArchivedOrders;",
+            ORDERS_BY_YEAR
+        ),
+    )
+    .unwrap();
+    assert!(output.contains("Int(0)"), "{}", output);
+}
+
+/// Runs a script against a fresh database file, returning the output of the
+/// last statement.
+fn run(name: &str, script: &str) -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!("banana_db_{}_{}.bin", name, std::process::id()));
+    let mut database = Database::new(path.to_str().unwrap());
+    let mut parser = Parser::new(Lexer::new(script)).unwrap();
+    let mut output = Ok(String::new());
+    for statement in parser.parse_script().unwrap() {
+        output = database.execute(statement.query);
+    }
+    database.delete().unwrap();
+    output
+}
+
+const BANNED_USERS: &str = "
+    new table Users {id: Int, banned: Boolean};
+    new table Orders {id: Int, user_id: Int};
+    insert {id: 1, banned: true} into Users;
+    insert {id: 2, banned: false} into Users;
+    insert {id: 3, banned: true} into Users;
+    insert {id: 10, user_id: 1} into Orders;
+    insert {id: 11, user_id: 2} into Orders;
+    insert {id: 12, user_id: 3} into Orders;
+";
+
+#[test]
+fn remove_in_subquery() {
+    let output = run(
+        "remove_in_subquery",
+        &format!(
+            "{} remove from Orders where user_id in (gimme {{id}} from Users where banned == true);",
+            BANNED_USERS
+        ),
+    )
+    .unwrap();
+    assert!(output.contains("Removed 2 row(s)"), "{}", output);
+}
+
+#[test]
+fn subquery_single_column() {
+    let err = run(
+        "subquery_single_column",
+        &format!(
+            "{} gimme Orders where user_id not in (gimme Users where banned == true);",
+            BANNED_USERS
+        ),
+    )
+    .unwrap_err();
+    assert!(err.contains("exactly 1 column"), "{}", err);
+}
+
+#[test]
+fn update_matching_rows() {
+    let mut table = users();
+    assert_eq!(
+        update(
+            &mut table,
+            "update Users set {name: Bob, age: 31} where id == 2;"
+        )
+        .unwrap(),
+        1
+    );
+    assert_eq!(
+        ids(
+            &table,
+            "gimme Users where name == Bob and age == 31 limit 10;"
+        ),
+        int_cells(&[2])
+    );
+    assert_eq!(
+        update(&mut table, "update Users set {age: 1} where age > 100;").unwrap(),
+        0
+    );
+    assert_eq!(update(&mut table, "update Users set {age: 1};").unwrap(), 3);
+}
+
+#[test]
+fn update_type_mismatch() {
+    let mut table = users();
+    let err = update(&mut table, "update Users set {age: old} where id == 2;");
+    assert!(matches!(err, Err(TableError::TypeMismatch(_, _))));
+    let err = update(&mut table, "update Users set {email: x} where id == 2;");
+    assert!(matches!(err, Err(TableError::FieldDoesNotExist(field)) if field == "email"));
+    assert_eq!(
+        ids(&table, "gimme Users where age == 24 limit 10;"),
+        int_cells(&[2])
+    );
+}
+
+#[test]
+fn remove_matching_rows() {
+    let mut table = users();
+    assert_eq!(
+        remove(&mut table, "remove from Users where age < 25;").unwrap(),
+        2
+    );
+    assert_eq!(ids(&table, "gimme Users limit 10;"), int_cells(&[1]));
+    assert!(remove(&mut table, "remove from Users where email == x;").is_err());
+    assert_eq!(ids(&table, "gimme Users limit 10;"), int_cells(&[1]));
+    assert_eq!(remove(&mut table, "remove all from Users;").unwrap(), 1);
+    assert!(ids(&table, "gimme Users limit 10;").is_empty());
+}
+
+#[test]
+fn open_unversioned_file() {
+    let path = std::env::temp_dir().join(format!("banana_db_legacy_{}.bin", std::process::id()));
+    let row = |id: i64, name: &str| legacy::Row {
+        values: HashMap::from([
+            (
+                "id".to_string(),
+                legacy::Cell {
+                    data: legacy::Data::Int(Some(id)),
+                },
+            ),
+            (
+                "name".to_string(),
+                legacy::Cell {
+                    data: legacy::Data::String(Some(name.to_string())),
+                },
+            ),
+        ]),
+    };
+    let records = vec![Record {
+        record_type: RecordType::Table,
+        data: legacy::Table {
+            name: "Users".to_string(),
+            columns: vec![
+                legacy::Column {
+                    name: "id".to_string(),
+                    datatype: legacy::Data::Int(None),
+                },
+                legacy::Column {
+                    name: "name".to_string(),
+                    datatype: legacy::Data::String(None),
+                },
+            ],
+            rows: vec![row(1, "Ann"), row(2, "Bo")],
+        },
+    }];
+    let encoded = bincode::serde::encode_to_vec(&records, bincode::config::standard()).unwrap();
+    std::fs::write(&path, encoded).unwrap();
+
+    let mut database = Database::new(path.to_str().unwrap());
+    let output = database
+        .handle_query("gimme Users where name == Bo;")
+        .unwrap();
+    assert!(output.contains("Int(2)"), "{}", output);
+    // the upgraded tables are written back in the current format
+    database
+        .handle_query("insert {id: 3, name: null} into Users;")
+        .unwrap();
+    let mut database = Database::new(path.to_str().unwrap());
+    let output = database
+        .handle_query("gimme count() from Users where name is null;")
+        .unwrap();
+    database.delete().unwrap();
+    assert!(output.contains("Int(1)"), "{}", output);
+}
+
+#[test]
+fn run_file_stops_at_parse_error() {
+    let dir = std::env::temp_dir();
+    let db_path = dir.join(format!("banana_db_run_file_{}.bin", std::process::id()));
+    let bql_path = dir.join(format!("banana_db_run_file_{}.bql", std::process::id()));
+    std::fs::write(
+        &bql_path,
+        "new table users { id: Int };\ninsert {id: 1} into users;\ngimme users where;\ninsert {id: 2} into users;",
+    )
+    .unwrap();
+
+    let err = bql::run_file(db_path.to_str().unwrap(), bql_path.to_str().unwrap()).unwrap_err();
+    assert!(err.contains("3:18"), "{}", err);
+
+    // the statements before the broken one ran, the ones after it did not
+    let mut database = Database::new(db_path.to_str().unwrap());
+    let output = database.handle_query("gimme users;").unwrap();
+    assert!(output.contains("Int(1)"), "{}", output);
+    assert!(!output.contains("Int(2)"), "{}", output);
+    database.delete().unwrap();
+    std::fs::remove_file(bql_path).unwrap();
+}
+
+#[test]
+fn run_file_stops_at_lexer_error() {
+    let dir = std::env::temp_dir();
+    let db_path = dir.join(format!("banana_db_run_lexer_{}.bin", std::process::id()));
+    let bql_path = dir.join(format!("banana_db_run_lexer_{}.bql", std::process::id()));
+    std::fs::write(
+        &bql_path,
+        "new table T {x: Int};\ninsert {x: 1} into T;\n@ gimme T;",
+    )
+    .unwrap();
+
+    let err = bql::run_file(db_path.to_str().unwrap(), bql_path.to_str().unwrap()).unwrap_err();
+    assert!(err.contains("3:1"), "{}", err);
+
+    let mut database = Database::new(db_path.to_str().unwrap());
+    let output = database.handle_query("gimme T;").unwrap();
+    assert!(output.contains("Int(1)"), "{}", output);
+    database.delete().unwrap();
+    std::fs::remove_file(bql_path).unwrap();
+}
+
+#[test]
+fn query_returns_typed_rows() {
+    let path = std::env::temp_dir().join(format!("banana_db_query_{}.bin", std::process::id()));
+    let mut database = Database::new(path.to_str().unwrap());
+    database
+        .handle_query("new table users { id: Int, name: String };")
+        .unwrap();
+    database
+        .handle_query("insert {id: 1, name: Ann} into users;")
+        .unwrap();
+
+    let rows = database
+        .query("gimme {id, name as who} from users;")
+        .unwrap();
+    assert_eq!(
+        rows.columns,
+        vec![
+            result_column("id", Some(DataType::Int)),
+            result_column("who", Some(DataType::String)),
+        ]
+    );
+    assert_eq!(
+        rows.rows[0].values["who"],
+        Cell::new(Data::String(Some("Ann".to_string())))
+    );
+
+    let err = database
+        .query("insert {id: 2, name: Bo} into users;")
+        .unwrap_err();
+    assert!(err.contains("Only `gimme` returns rows"), "{}", err);
+    let err = database.query("gimme users; gimme users;").unwrap_err();
+    assert!(err.contains("Expected exactly one statement"), "{}", err);
+    database.delete().unwrap();
+}
+
+#[test]
+fn handle_query_runs_every_statement() {
+    let path =
+        std::env::temp_dir().join(format!("banana_db_statements_{}.bin", std::process::id()));
+    let mut database = Database::new(path.to_str().unwrap());
+    let output = database
+        .handle_query("new table T {x: Int}; insert {x: 1} into T; insert {x: 2} into T;")
+        .unwrap();
+    assert_eq!(output.matches("Inserted 1 row(s)").count(), 2, "{}", output);
+    let output = database.handle_query("gimme count() from T;").unwrap();
+    assert!(output.contains("Int(2)"), "{}", output);
+
+    // a statement that doesn't parse stops the whole line
+    assert!(
+        database
+            .handle_query("insert {x: 3} into T; gimme T where;")
+            .is_err()
+    );
+    let output = database.handle_query("gimme count() from T;").unwrap();
+    assert!(output.contains("Int(2)"), "{}", output);
+    database.delete().unwrap();
+}
+
+#[test]
+fn join_has_no_cursor() {
+    let path =
+        std::env::temp_dir().join(format!("banana_db_join_cursor_{}.bin", std::process::id()));
+    let mut database = Database::new(path.to_str().unwrap());
+    database
+        .handle_query(
+            "new table O {oid: Int, uid: Int}; new table P {id: Int, n: String};
+            insert [{oid: 1, uid: 1}, {oid: 2, uid: 2}] into O;
+            insert [{id: 1, n: a}, {id: 2, n: b}] into P;",
+        )
+        .unwrap();
+    let rows = database
+        .query("gimme {oid, n} from O join P on uid == id limit 1;")
+        .unwrap();
+    assert_eq!(rows.rows.len(), 1);
+    assert!(rows.cursor.is_none());
+
+    let cursor = database.query("gimme O limit 1;").unwrap().cursor.unwrap();
+    let err = database
+        .query(&format!(
+            "gimme {{oid, n}} from O join P on uid == id limit 1 after {};",
+            cursor
+        ))
+        .unwrap_err();
+    assert!(err.contains("is not valid for this query"), "{}", err);
+    database.delete().unwrap();
+}
+
+#[test]
+fn sort_cmp_nulls() {
+    assert_eq!(Data::Int(None).sort_cmp(&Data::Null), Ordering::Equal);
+    assert_eq!(
+        Data::String(None).sort_cmp(&Data::String(Some("a".to_string()))),
+        Ordering::Less
+    );
+    assert_eq!(
+        Data::Float(Some(f64::NEG_INFINITY)).sort_cmp(&Data::Float(None)),
+        Ordering::Greater
+    );
+    assert_eq!(
+        Data::Float(Some(-0.0)).sort_cmp(&Data::Float(Some(0.0))),
+        Ordering::Less
+    );
+}
+
+#[test]
+fn open_unreadable_file() {
+    let path =
+        std::env::temp_dir().join(format!("banana_db_unreadable_{}.bin", std::process::id()));
+    let mut newer = b"BNDB".to_vec();
+    newer.extend(99u32.to_le_bytes());
+    for (contents, message) in [
+        (newer, "version 99 is newer than the supported version"),
+        (b"BNDB\x02\0\0\0\xff".to_vec(), ""),
+    ] {
+        std::fs::write(&path, contents).unwrap();
+        let mut database = Database::new(path.to_str().unwrap());
+        let err = database.handle_query("tables;").unwrap_err();
+        assert!(err.contains(message), "{}", err);
+    }
+    std::fs::remove_file(path).unwrap();
+}
@@ -15,7 +15,12 @@ fn main() {
     let args = Args::parse();
 
     match args.run_file {
-        Some(file) => bql::run_file(&args.file_name, &file).expect("Failed to run file"),
+        Some(file) => {
+            if let Err(err) = bql::run_file(&args.file_name, &file) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
         None => repl::start(&args.file_name).expect("Failed to start REPL"),
     }
 }
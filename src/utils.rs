@@ -1,13 +1,30 @@
 use colored::Colorize;
 use tabled::settings::{Color, Settings, Style, object::Rows};
 
+use crate::bql::token::LineColumn;
+
 pub fn format_line_section_highlight(input: &str, start_index: usize, end_index: usize) -> String {
-    format!(
+    let start_index = start_index.min(input.len());
+    let line_start = input[..start_index].rfind('\n').map_or(0, |i| i + 1);
+    let line = input[line_start..].lines().next().unwrap_or("");
+    let location = LineColumn::from_index(input, start_index);
+
+    // only point at the part of the section that is on the first line
+    let width = (end_index.max(start_index) - start_index + 1)
+        .min(line.len().saturating_sub(start_index - line_start))
+        .max(1);
+
+    let mut out = String::new();
+    if input.trim_end().contains('\n') {
+        out.push_str(&format!("\n\t{}", location.to_string().dimmed()));
+    }
+    out.push_str(&format!(
         "\n\t{}\n\t{}{}",
-        input.trim().dimmed(),
-        " ".repeat(start_index),
-        "^".repeat(end_index - start_index + 1).red().bold()
-    )
+        line.trim_end().dimmed(),
+        " ".repeat(location.column - 1),
+        "^".repeat(width).red().bold()
+    ));
+    out
 }
 
 pub fn format_message(module: &str, message: &str) -> String {